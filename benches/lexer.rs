@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use script_parser::{Lexer, Parser};
+use std::hint::black_box;
+
+#[path = "baseline/lexer.rs"]
+mod baseline;
+
+/// Roughly the number of lines on a printed screenplay page.
+const LINES_PER_PAGE: usize = 55;
+
+/// Builds a script of about `pages` pages, cycling through every kind of
+/// script element.
+fn long_script(pages: usize) -> String {
+    let mut script = String::from(
+        "# The Long Goodbye\n\
+         Author: Jane Doe\n\
+         Draft date: 1 March 2024\n\
+         \n\
+         ## Characters\n\
+         \n\
+         ALICE: Alice Johnson\n  \
+           age: 30s\n  \
+           aliases: AL\n\
+         BOB: Bob Smith\n\
+         DR_LEE: Doctor Lee\n\
+         \n\
+         ## Script\n\
+         \n",
+    );
+
+    let mut lines = 0;
+    let mut scene = 0;
+    while lines < pages * LINES_PER_PAGE {
+        scene += 1;
+        script.push_str(&format!("[Living Room - Scene {}]\n\n", scene));
+        script.push_str("The lights are dim, casting long shadows across the room.\n\n");
+        script.push_str("ALICE: I can't believe you're actually leaving.\n\n");
+        script.push_str("BOB: (quietly) I don't have a choice, Alice. The job in New York\n");
+        script.push_str("  is too good to pass up.\n\n");
+        script.push_str("(Alice turns away, looking out the window)\n\n");
+        script.push_str("DR_LEE: Both of you, sit down. (beat) Please.\n\n");
+        script.push_str("N: The silence stretches between them, heavy with unspoken words.\n\n");
+        lines += 16;
+    }
+    script
+}
+
+fn tokenize(c: &mut Criterion) {
+    let source = long_script(120);
+    let mut group = c.benchmark_group("120 pages");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+
+    group.bench_with_input(BenchmarkId::new("tokenize", "grammar"), &source, |b, source| {
+        b.iter(|| Lexer::new(black_box(source.clone())).tokenize())
+    });
+    group.bench_with_input(BenchmarkId::new("tokenize", "baseline"), &source, |b, source| {
+        b.iter(|| baseline::Lexer::new(black_box(source.clone())).tokenize())
+    });
+    group.bench_with_input(BenchmarkId::new("parse", "grammar"), &source, |b, source| {
+        b.iter(|| {
+            let tokens = Lexer::new(black_box(source.clone())).tokenize();
+            Parser::new(tokens).parse().unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, tokenize);
+criterion_main!(benches);
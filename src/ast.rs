@@ -1,45 +1,387 @@
-use std::collections::HashMap;
-use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+/// A location in the source file. `line` and `column` are 1-based and point at
+/// the first character; `byte_range` covers the whole construct.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub byte_range: Range<usize>,
+}
+
+/// A parsed script. It serializes to the JSON and YAML documents of
+/// `script-parser convert`; spans and empty lists may be left out when
+/// deserializing.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Script {
-    pub title_section: String,
-    pub characters: HashMap<String, String>,
+    pub title_page: Option<TitlePage>,
+    #[serde(default)]
+    pub characters: CharacterTable,
+    #[serde(default)]
     pub scenes: Vec<Scene>,
+    /// Inline `<!-- script-parser: ... -->` comments that silence validator rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+}
+
+/// Character code reserved for the narrator. Lines spoken by it are parsed
+/// as narration rather than dialogue.
+pub const NARRATOR_CODE: &str = "N";
+
+/// Metadata keys recognised in the title section, matched case-insensitively.
+pub const TITLE_PAGE_KEYS: &[&str] = &[
+    "title", "credit", "author", "authors", "draft date", "contact", "copyright",
+];
+
+/// Contents of the `# ...` title section: the heading plus any recognised
+/// `Key: value` metadata lines. Other lines are kept, in order, as notes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitlePage {
+    pub title: String,
+    pub credit: Option<String>,
+    pub author: Option<String>,
+    pub draft_date: Option<String>,
+    pub contact: Option<String>,
+    pub copyright: Option<String>,
+    pub notes: Vec<String>,
+    pub span: Span,
+}
+
+/// Metadata keys recognised on indented lines below a character definition.
+pub const CHARACTER_ATTRIBUTE_KEYS: &[&str] = &["description", "age", "aliases"];
+
+/// A character declared in the `## Characters` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Character {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub age: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// The characters of a script, kept in declaration order. Later definitions
+/// of an already defined code are kept separately so they can be reported.
+/// Serialized as a plain list of definitions, duplicates last; reading the
+/// list back inserts them in order, so the duplicates are found again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CharacterTable {
+    characters: Vec<Character>,
+    duplicates: Vec<Character>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Scene {
     pub location: Option<String>,
+    #[serde(default)]
     pub elements: Vec<ScriptElement>,
+    #[serde(default)]
+    pub span: Span,
 }
 
+/// Serialized with a `type` field naming the kind of element, e.g.
+/// `{"type": "dialogue", "speaker": "ALICE", ...}`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ScriptElement {
     Dialogue { 
         speaker: String, 
         text: String, 
-        actions: Vec<String> 
+        #[serde(default)]
+        actions: Vec<Parenthetical>,
+        #[serde(default)]
+        span: Span,
     },
-    Narration(String),
-    Action(String),
+    Narration {
+        text: String,
+        /// The narrator code the line was introduced with, e.g. `N:`
+        voice: Option<String>,
+        #[serde(default)]
+        span: Span,
+    },
+    Action {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+    /// A transition between scenes, such as `CUT TO:`
+    Transition {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+}
+
+/// A wryly attached to a line of dialogue, such as `(whispering)`. `offset` is
+/// the byte position in the dialogue text at which the parenthetical occurs,
+/// so `0` places it directly under the character cue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parenthetical {
+    pub text: String,
+    pub offset: usize,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// An inline comment disabling validator rules, either for the whole file
+/// (`<!-- script-parser: disable unused-character -->`) or for the line after
+/// it (`disable-next-line`). An empty rule list disables every rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suppression {
+    pub rules: Vec<String>,
+    pub scope: SuppressionScope,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SuppressionScope {
+    File,
+    NextLine,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, byte_range: Range<usize>) -> Self {
+        Span { line, column, byte_range }
+    }
+
+    /// Returns a span starting at `self` and extending to the end of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+            byte_range: self.byte_range.start..other.byte_range.end.max(self.byte_range.end),
+        }
+    }
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Script {
     pub fn new() -> Self {
         Script {
-            title_section: String::new(),
-            characters: HashMap::new(),
+            title_page: None,
+            characters: CharacterTable::new(),
             scenes: Vec::new(),
+            suppressions: Vec::new(),
+        }
+    }
+
+    /// Whether an inline comment disables `rule` at `span`. Findings without
+    /// a location can only be silenced for the whole file.
+    pub fn is_suppressed(&self, rule: &str, span: Option<&Span>) -> bool {
+        self.suppressions.iter().any(|suppression| {
+            let applies = match suppression.scope {
+                SuppressionScope::File => true,
+                SuppressionScope::NextLine => span.is_some_and(|span| span.line == suppression.span.line + 1),
+            };
+            applies && (suppression.rules.is_empty() || suppression.rules.iter().any(|id| id == rule))
+        })
+    }
+}
+
+impl Suppression {
+    pub fn new(rules: Vec<String>, scope: SuppressionScope, span: Span) -> Self {
+        Suppression { rules, scope, span }
+    }
+}
+
+impl TitlePage {
+    pub fn new(title: String, span: Span) -> Self {
+        TitlePage {
+            title,
+            span,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the metadata field named by `key` (case-insensitive). Repeated keys
+    /// append a new line to the existing value. Returns `false` for unknown keys.
+    pub fn set_field(&mut self, key: &str, value: &str) -> bool {
+        let field = match key.to_lowercase().as_str() {
+            "title" => {
+                self.title = value.to_string();
+                return true;
+            }
+            "credit" => &mut self.credit,
+            "author" | "authors" => &mut self.author,
+            "draft date" => &mut self.draft_date,
+            "contact" => &mut self.contact,
+            "copyright" => &mut self.copyright,
+            _ => return false,
+        };
+        match field {
+            Some(existing) => {
+                existing.push('\n');
+                existing.push_str(value);
+            }
+            None => *field = Some(value.to_string()),
+        }
+        true
+    }
+}
+
+impl Character {
+    pub fn new(code: String, name: String, span: Span) -> Self {
+        Character {
+            code,
+            name,
+            description: None,
+            age: None,
+            aliases: Vec::new(),
+            span,
         }
     }
+
+    /// Sets the attribute named by `key` (case-insensitive). Aliases are given
+    /// as a comma-separated list. Returns `false` for unknown keys.
+    pub fn set_attribute(&mut self, key: &str, value: &str) -> bool {
+        match key.to_lowercase().as_str() {
+            "description" => self.description = Some(value.to_string()),
+            "age" => self.age = Some(value.to_string()),
+            "aliases" => self.aliases.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|alias| !alias.is_empty())
+                    .map(str::to_string),
+            ),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl CharacterTable {
+    pub fn new() -> Self {
+        CharacterTable {
+            characters: Vec::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    /// Adds a character at the end of the table. If the code is already
+    /// defined, the first definition is kept and this one is recorded as a
+    /// duplicate.
+    pub fn insert(&mut self, character: Character) {
+        if self.contains(&character.code) {
+            self.duplicates.push(character);
+        } else {
+            self.characters.push(character);
+        }
+    }
+
+    /// Definitions that repeat a code already defined earlier in the table.
+    pub fn duplicates(&self) -> &[Character] {
+        &self.duplicates
+    }
+
+    pub fn get(&self, code: &str) -> Option<&Character> {
+        self.characters.iter().find(|character| character.code == code)
+    }
+
+    /// Looks up a character by its code or one of its aliases.
+    pub fn resolve(&self, code_or_alias: &str) -> Option<&Character> {
+        self.get(code_or_alias).or_else(|| {
+            self.characters
+                .iter()
+                .find(|character| character.aliases.iter().any(|alias| alias == code_or_alias))
+        })
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.get(code).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.characters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Character> {
+        self.characters.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CharacterTable {
+    type Item = &'a Character;
+    type IntoIter = std::slice::Iter<'a, Character>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.characters.iter()
+    }
+}
+
+impl Serialize for CharacterTable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.characters.iter().chain(&self.duplicates))
+    }
+}
+
+impl<'de> Deserialize<'de> for CharacterTable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table = CharacterTable::new();
+        for character in Vec::<Character>::deserialize(deserializer)? {
+            table.insert(character);
+        }
+        Ok(table)
+    }
 }
 
 impl Scene {
-    pub fn new(location: Option<String>) -> Self {
+    pub fn new(location: Option<String>, span: Span) -> Self {
         Scene {
             location,
             elements: Vec::new(),
+            span,
         }
     }
-}
\ No newline at end of file
+
+    /// Appends an element, extending the scene's span to cover it. A scene
+    /// without a location header starts at its first element.
+    pub fn push(&mut self, element: ScriptElement) {
+        if self.location.is_none() && self.elements.is_empty() {
+            self.span = element.span().clone();
+        } else {
+            self.span = self.span.to(element.span());
+        }
+        self.elements.push(element);
+    }
+}
+
+impl ScriptElement {
+    pub fn span(&self) -> &Span {
+        match self {
+            ScriptElement::Dialogue { span, .. } => span,
+            ScriptElement::Narration { span, .. } => span,
+            ScriptElement::Action { span, .. } => span,
+            ScriptElement::Transition { span, .. } => span,
+        }
+    }
+}
+
+impl Parenthetical {
+    /// The byte position in `text` at which the parenthetical occurs, for a
+    /// caller that has already consumed the text up to `from`. The offset is
+    /// kept between `from` and the end of the text and moved back to a
+    /// character boundary, so the text can always be sliced at it.
+    pub fn position_in(&self, text: &str, from: usize) -> usize {
+        let mut offset = self.offset.clamp(from, text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
@@ -0,0 +1,215 @@
+use crate::ast::Span;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A secondary location that helps explain a diagnostic, such as the first
+/// definition of a duplicated character code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelatedLocation {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A replacement of the source bytes in `byte_range` with `replacement`.
+/// An empty replacement deletes the range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextEdit {
+    pub byte_range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Advice on how to resolve a diagnostic. When `edits` is non-empty the
+/// suggestion can be applied mechanically.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub message: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A single finding reported by the validator. `code` is the stable identifier
+/// of the rule that produced it, e.g. `undefined-speaker`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<Span>,
+    pub suggestion: Option<Suggestion>,
+    pub related: Vec<RelatedLocation>,
+}
+
+/// The outcome of validating a script: every diagnostic, in the order found.
+/// Findings are keyed by rule and location, so each issue is reported once
+/// even when several checks come across it. Findings without a location are
+/// all kept, as there is nothing to tell them apart by.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    diagnostics: Vec<Diagnostic>,
+    #[serde(skip)]
+    seen: HashSet<(&'static str, Span)>,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl TextEdit {
+    pub fn new(byte_range: Range<usize>, replacement: String) -> Self {
+        TextEdit { byte_range, replacement }
+    }
+}
+
+impl Suggestion {
+    pub fn new(message: String) -> Self {
+        Suggestion {
+            message,
+            edits: Vec::new(),
+        }
+    }
+
+    pub fn with_edit(mut self, edit: TextEdit) -> Self {
+        self.edits.push(edit);
+        self
+    }
+
+    pub fn is_fixable(&self) -> bool {
+        !self.edits.is_empty()
+    }
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message,
+            span: None,
+            suggestion: None,
+            related: Vec::new(),
+        }
+    }
+
+    pub fn error(code: &'static str, message: String) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: &'static str, message: String) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    pub fn with_span(mut self, span: &Span) -> Self {
+        self.span = Some(span.clone());
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: String) -> Self {
+        self.suggestion = Some(Suggestion::new(suggestion));
+        self
+    }
+
+    /// Attaches a suggestion whose edits resolve the diagnostic.
+    pub fn with_fix(mut self, suggestion: Suggestion) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    pub fn with_related(mut self, span: &Span, message: String) -> Self {
+        self.related.push(RelatedLocation {
+            span: span.clone(),
+            message,
+        });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity.to_string().to_uppercase(), self.code, self.message)?;
+
+        if let Some(span) = &self.span {
+            write!(f, " (line {}, column {})", span.line, span.column)?;
+        }
+
+        for related in &self.related {
+            write!(
+                f,
+                "\n  Note: {} (line {}, column {})",
+                related.message, related.span.line, related.span.column
+            )?;
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  Suggestion: {}", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        ValidationReport {
+            diagnostics: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Adds `diagnostic` unless the same rule has already reported an issue
+    /// at the same location. Returns whether it was added.
+    pub fn push(&mut self, diagnostic: Diagnostic) -> bool {
+        // A hand-written AST may leave spans out, leaving every finding at
+        // line 0
+        if let Some(span) = &diagnostic.span
+            && span.line > 0
+            && !self.seen.insert((diagnostic.code, span.clone()))
+        {
+            return false;
+        }
+        self.diagnostics.push(diagnostic);
+        true
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
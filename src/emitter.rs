@@ -0,0 +1,156 @@
+use crate::ast::Span;
+use crate::diagnostic::{Diagnostic, Severity};
+use std::fmt::Write;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+/// Formats diagnostics as annotated reports in the style of rustc: the
+/// message, the offending source line with the span underlined, any related
+/// locations and the suggestion as a help note.
+pub struct Emitter<'a> {
+    source: &'a str,
+    file_name: &'a str,
+    color: bool,
+}
+
+impl<'a> Emitter<'a> {
+    pub fn new(source: &'a str, file_name: &'a str) -> Self {
+        Emitter {
+            source,
+            file_name,
+            color: false,
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        // Width of the line-number gutter, shared by every snippet in the report
+        let gutter = diagnostic
+            .span
+            .iter()
+            .chain(diagnostic.related.iter().map(|related| &related.span))
+            .map(|span| span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let _ = writeln!(
+            out,
+            "{}{}[{}]{}{}: {}{}",
+            self.paint(severity_color),
+            diagnostic.severity,
+            diagnostic.code,
+            self.paint(RESET),
+            self.paint(BOLD),
+            diagnostic.message,
+            self.paint(RESET),
+        );
+
+        if let Some(span) = &diagnostic.span {
+            self.snippet(&mut out, span, gutter, "-->", severity_color, "");
+        }
+
+        for related in &diagnostic.related {
+            self.snippet(&mut out, &related.span, gutter, ":::", BLUE, &related.message);
+        }
+
+        if let Some(suggestion) = &diagnostic.suggestion {
+            let _ = writeln!(
+                out,
+                "{:gutter$} {}={} {}help{}: {}",
+                "",
+                self.paint(BLUE),
+                self.paint(RESET),
+                self.paint(CYAN),
+                self.paint(RESET),
+                suggestion,
+            );
+        }
+
+        out
+    }
+
+    /// Writes the `--> file:line:column` header (`:::` for related locations)
+    /// and the source line with the span underlined and labelled.
+    fn snippet(
+        &self,
+        out: &mut String,
+        span: &Span,
+        gutter: usize,
+        arrow: &str,
+        color: &'static str,
+        label: &str,
+    ) {
+        // A hand-written AST may leave spans out, so there is no location
+        if span.line == 0 {
+            return;
+        }
+
+        let blue = self.paint(BLUE);
+        let reset = self.paint(RESET);
+
+        let _ = writeln!(
+            out,
+            "{:gutter$}{}{}{} {}:{}:{}",
+            "", blue, arrow, reset, self.file_name, span.line, span.column,
+        );
+
+        let Some((line_text, start, width)) = self.locate(span) else {
+            return;
+        };
+
+        let _ = writeln!(out, "{:gutter$} {}|{}", "", blue, reset);
+        let _ = writeln!(out, "{}{:>gutter$} |{} {}", blue, span.line, reset, line_text);
+        let _ = writeln!(
+            out,
+            "{:gutter$} {}|{} {}{}{}{}{}",
+            "",
+            blue,
+            reset,
+            " ".repeat(start),
+            self.paint(color),
+            "^".repeat(width),
+            if label.is_empty() { String::new() } else { format!(" {}", label) },
+            reset,
+        );
+        let _ = writeln!(out, "{:gutter$} {}|{}", "", blue, reset);
+    }
+
+    /// Finds the source line containing the start of `span`, returning the line
+    /// text, the column the span starts at and how many characters to underline
+    /// on that line. Nothing is quoted when there is no source.
+    fn locate(&self, span: &Span) -> Option<(&str, usize, usize)> {
+        let offset = span.byte_range.start;
+        if self.source.is_empty() || offset > self.source.len() || !self.source.is_char_boundary(offset) {
+            return None;
+        }
+
+        let line_start = self.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.source[offset..].find('\n').map_or(self.source.len(), |i| offset + i);
+        let line_text = self.source[line_start..line_end].trim_end_matches('\r');
+
+        let start = self.source[line_start..offset].chars().count();
+        let end = span.byte_range.end.clamp(offset, line_start + line_text.len());
+        let width = self.source[offset..end].chars().count().max(1);
+
+        Some((line_text, start, width))
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.color { code } else { "" }
+    }
+}
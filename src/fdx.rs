@@ -0,0 +1,473 @@
+use crate::ast::{Character, Parenthetical, Scene, Script, ScriptElement, Span, TitlePage};
+use crate::fountain::character_name;
+use crate::parser::NarratorCodes;
+use crate::renderer::{dialogue_parts, DialoguePart, RenderOptions, Renderer};
+use anyhow::{anyhow, Result};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::io::Write;
+use std::ops::Range;
+
+/// Writes a script as a Final Draft (`.fdx`) document. Scene headings,
+/// action, character cues, parentheticals, dialogue and transitions each
+/// become a paragraph of the matching type; the cast list becomes the
+/// document's SmartType character list.
+pub struct FdxRenderer;
+
+/// Reads a Final Draft (`.fdx`) document into the AST, the inverse of
+/// [`FdxRenderer`].
+///
+/// FDX keeps no full names for characters, so the character table is built
+/// from the SmartType character list and the character cues, with names
+/// derived from the codes. The free-form title page is read back assuming
+/// the layout [`FdxRenderer`] writes.
+pub struct FdxParser {
+    source: String,
+    narrator_codes: NarratorCodes,
+}
+
+/// A paragraph of the document with its text runs joined.
+#[derive(Debug, Default)]
+struct FdxParagraph {
+    kind: String,
+    alignment: String,
+    text: String,
+    span: Span,
+}
+
+impl Default for FdxRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FdxRenderer {
+    pub fn new() -> Self {
+        FdxRenderer
+    }
+}
+
+impl Renderer for FdxRenderer {
+    fn name(&self) -> &'static str {
+        "fdx"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "fdx"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, _options: &RenderOptions) -> Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\" ?>")?;
+        writeln!(out, "<FinalDraft DocumentType=\"Script\" Template=\"No\" Version=\"5\">")?;
+        writeln!(out, "  <Content>")?;
+
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location {
+                write_paragraph(out, "Scene Heading", location)?;
+            }
+
+            for element in &scene.elements {
+                match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        write_speech(out, speaker, text, actions)?;
+                    }
+                    ScriptElement::Narration { text, voice: Some(voice), .. } => {
+                        write_speech(out, voice, text, &[])?;
+                    }
+                    ScriptElement::Narration { text, voice: None, .. } => {
+                        for paragraph in text.split('\n').map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+                            write_paragraph(out, "Action", paragraph)?;
+                        }
+                    }
+                    ScriptElement::Action { text, .. } => {
+                        write_paragraph(out, "Action", &format!("({})", text.replace('\n', " ")))?;
+                    }
+                    ScriptElement::Transition { text, .. } => {
+                        write_paragraph(out, "Transition", text)?;
+                    }
+                }
+            }
+        }
+
+        writeln!(out, "  </Content>")?;
+
+        if let Some(title_page) = &script.title_page {
+            writeln!(out, "  <TitlePage>")?;
+            writeln!(out, "    <Content>")?;
+            write_title_page(out, title_page)?;
+            writeln!(out, "    </Content>")?;
+            writeln!(out, "  </TitlePage>")?;
+        }
+
+        if !script.characters.is_empty() {
+            writeln!(out, "  <SmartType>")?;
+            writeln!(out, "    <Characters>")?;
+            for character in &script.characters {
+                writeln!(out, "      <Character>{}</Character>", escape(character.code.as_str()))?;
+            }
+            writeln!(out, "    </Characters>")?;
+            writeln!(out, "  </SmartType>")?;
+        }
+
+        writeln!(out, "</FinalDraft>")?;
+        Ok(())
+    }
+}
+
+fn write_paragraph(out: &mut dyn Write, kind: &str, text: &str) -> Result<()> {
+    writeln!(out, "    <Paragraph Type=\"{}\">", kind)?;
+    writeln!(out, "      <Text>{}</Text>", escape(text))?;
+    writeln!(out, "    </Paragraph>")?;
+    Ok(())
+}
+
+/// A title page paragraph, which has no type, only an alignment.
+fn write_title_line(out: &mut dyn Write, alignment: &str, text: &str) -> Result<()> {
+    writeln!(out, "      <Paragraph Alignment=\"{}\">", alignment)?;
+    writeln!(out, "        <Text>{}</Text>", escape(text))?;
+    writeln!(out, "      </Paragraph>")?;
+    Ok(())
+}
+
+/// A Character paragraph followed by the speech, with a Dialogue paragraph
+/// per paragraph of text and the parentheticals where they occurred.
+fn write_speech(out: &mut dyn Write, speaker: &str, text: &str, actions: &[Parenthetical]) -> Result<()> {
+    write_paragraph(out, "Character", speaker)?;
+    for part in dialogue_parts(text, actions) {
+        match part {
+            DialoguePart::Speech(speech) => {
+                for paragraph in speech.split('\n').map(str::trim).filter(|paragraph| !paragraph.is_empty()) {
+                    write_paragraph(out, "Dialogue", paragraph)?;
+                }
+            }
+            DialoguePart::Parenthetical(wryly) => {
+                write_paragraph(out, "Parenthetical", &format!("({})", wryly))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The title, credit, author and notes centred, the contact details on the
+/// left and the draft date and copyright notice on the right.
+fn write_title_page(out: &mut dyn Write, title_page: &TitlePage) -> Result<()> {
+    let one_line = |value: &String| value.lines().collect::<Vec<_>>().join(" ");
+
+    write_title_line(out, "Center", &title_page.title)?;
+    for value in [&title_page.credit, &title_page.author].into_iter().flatten() {
+        write_title_line(out, "Center", &one_line(value))?;
+    }
+    for note in &title_page.notes {
+        write_title_line(out, "Center", note)?;
+    }
+    if let Some(contact) = &title_page.contact {
+        for line in contact.lines() {
+            write_title_line(out, "Left", line)?;
+        }
+    }
+    for value in [&title_page.draft_date, &title_page.copyright].into_iter().flatten() {
+        write_title_line(out, "Right", &one_line(value))?;
+    }
+    Ok(())
+}
+
+impl FdxParser {
+    pub fn new(source: String) -> Self {
+        FdxParser {
+            source,
+            narrator_codes: NarratorCodes::default(),
+        }
+    }
+
+    /// Adds character codes whose speeches are parsed as narration, in
+    /// addition to the reserved `N` code.
+    pub fn with_narrator_codes<I, S>(mut self, codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.narrator_codes.extend(codes);
+        self
+    }
+
+    pub fn parse(&self) -> Result<Script> {
+        let mut reader = Reader::from_str(&self.source);
+        let mut is_final_draft = false;
+        let mut in_title_page = false;
+        let mut in_smart_type = false;
+        let mut in_text = false;
+        // The paragraph or SmartType character being read, and where it started
+        let mut paragraph: Option<FdxParagraph> = None;
+        let mut cast_member: Option<(String, usize)> = None;
+
+        let mut content = Vec::new();
+        let mut title_page = Vec::new();
+        let mut cast = Vec::new();
+        let mut lines = LineTracker::default();
+
+        loop {
+            let start = reader.buffer_position() as usize;
+            match reader.read_event()? {
+                Event::Start(element) => match element.name().as_ref() {
+                    b"FinalDraft" => is_final_draft = true,
+                    b"TitlePage" => in_title_page = true,
+                    b"SmartType" => in_smart_type = true,
+                    b"Paragraph" => {
+                        let span = lines.span(&self.source, start..start);
+                        paragraph = Some(paragraph_start(&element, span)?);
+                    }
+                    b"Text" => in_text = paragraph.is_some(),
+                    b"Character" if in_smart_type => cast_member = Some((String::new(), start)),
+                    _ => {}
+                },
+                Event::Empty(element) if element.name().as_ref() == b"Paragraph" => {
+                    let span = lines.span(&self.source, start..reader.buffer_position() as usize);
+                    let empty = paragraph_start(&element, span)?;
+                    let paragraphs = if in_title_page { &mut title_page } else { &mut content };
+                    paragraphs.push(empty);
+                }
+                Event::Text(text) => {
+                    if let Some(paragraph) = paragraph.as_mut().filter(|_| in_text) {
+                        paragraph.text.push_str(&text.unescape()?);
+                    } else if let Some((code, _)) = cast_member.as_mut() {
+                        code.push_str(&text.unescape()?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(paragraph) = paragraph.as_mut().filter(|_| in_text) {
+                        paragraph.text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::End(element) => match element.name().as_ref() {
+                    b"TitlePage" => in_title_page = false,
+                    b"SmartType" => in_smart_type = false,
+                    b"Text" => in_text = false,
+                    b"Paragraph" => {
+                        if let Some(mut finished) = paragraph.take() {
+                            let end = reader.buffer_position() as usize;
+                            finished.span = lines.span(&self.source, finished.span.byte_range.start..end);
+                            let paragraphs = if in_title_page { &mut title_page } else { &mut content };
+                            paragraphs.push(finished);
+                        }
+                    }
+                    b"Character" => {
+                        if let Some((code, start)) = cast_member.take() {
+                            let span = lines.span(&self.source, start..reader.buffer_position() as usize);
+                            cast.push((code.trim().to_string(), span));
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if !is_final_draft {
+            return Err(anyhow!("Not a Final Draft document: missing <FinalDraft> element"));
+        }
+
+        let mut script = Script::new();
+        script.title_page = parse_title_page(&title_page);
+        for (code, span) in cast {
+            if !code.is_empty() && !script.characters.contains(&code) {
+                let name = character_name(&code);
+                script.characters.insert(Character::new(code, name, span));
+            }
+        }
+        self.parse_content(&mut script, &content);
+        Ok(script)
+    }
+
+    fn parse_content(&self, script: &mut Script, paragraphs: &[FdxParagraph]) {
+        let mut scene = Scene::new(None, Span::default());
+        let mut index = 0;
+
+        while let Some(paragraph) = paragraphs.get(index) {
+            let text = paragraph.text.trim();
+            index += 1;
+            if text.is_empty() {
+                continue;
+            }
+
+            match paragraph.kind.as_str() {
+                "Scene Heading" => {
+                    let next_scene = Scene::new(Some(text.to_string()), paragraph.span.clone());
+                    let previous = std::mem::replace(&mut scene, next_scene);
+                    if !previous.elements.is_empty() || previous.location.is_some() {
+                        script.scenes.push(previous);
+                    }
+                }
+                "Character" => {
+                    // Extensions such as `(V.O.)` are not part of the code
+                    let speaker = text.split_once('(').map_or(text, |(code, _extension)| code).trim();
+                    let speaker = speaker.trim_end_matches('^').trim_end().to_string();
+                    let (element, next) = self.parse_speech(paragraphs, index, paragraph, speaker);
+                    if let ScriptElement::Dialogue { speaker, .. } = &element
+                        && !script.characters.contains(speaker)
+                    {
+                        let name = character_name(speaker);
+                        script.characters.insert(Character::new(speaker.clone(), name, paragraph.span.clone()));
+                    }
+                    scene.push(element);
+                    index = next;
+                }
+                "Transition" => scene.push(ScriptElement::Transition {
+                    text: text.to_string(),
+                    span: paragraph.span.clone(),
+                }),
+                // Action, and anything this AST has no element for
+                _ => scene.push(match text.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+                    Some(inner) if paragraph.kind == "Action" => ScriptElement::Action {
+                        text: inner.trim().to_string(),
+                        span: paragraph.span.clone(),
+                    },
+                    _ => ScriptElement::Narration {
+                        text: text.to_string(),
+                        voice: None,
+                        span: paragraph.span.clone(),
+                    },
+                }),
+            }
+        }
+
+        if !scene.elements.is_empty() || scene.location.is_some() {
+            script.scenes.push(scene);
+        }
+    }
+
+    /// Reads the Dialogue and Parenthetical paragraphs from `index` on as the
+    /// speech of `speaker`. Consecutive Dialogue paragraphs are separate
+    /// paragraphs of the speech. Returns the element and the index after it.
+    fn parse_speech(
+        &self,
+        paragraphs: &[FdxParagraph],
+        index: usize,
+        cue: &FdxParagraph,
+        speaker: String,
+    ) -> (ScriptElement, usize) {
+        let mut span = cue.span.clone();
+        let mut text = String::new();
+        let mut actions = Vec::new();
+        let mut separator = "\n";
+        let mut index = index;
+
+        while let Some(paragraph) = paragraphs.get(index) {
+            let content = paragraph.text.trim();
+            match paragraph.kind.as_str() {
+                "Parenthetical" => {
+                    let inner = content.strip_prefix('(').unwrap_or(content);
+                    let inner = inner.strip_suffix(')').unwrap_or(inner);
+                    actions.push(Parenthetical {
+                        text: inner.trim().to_string(),
+                        offset: if text.is_empty() { 0 } else { text.len() + 1 },
+                        span: paragraph.span.clone(),
+                    });
+                    separator = " ";
+                }
+                "Dialogue" => {
+                    if !content.is_empty() {
+                        if !text.is_empty() {
+                            text.push_str(separator);
+                        }
+                        text.push_str(content);
+                    }
+                    separator = "\n";
+                }
+                _ => break,
+            }
+            span = span.to(&paragraph.span);
+            index += 1;
+        }
+
+        (self.narrator_codes.speech(speaker, text, actions, span), index)
+    }
+}
+
+/// A paragraph with the type and alignment of `element`, starting at `span`
+/// and to be completed when it ends.
+fn paragraph_start(element: &BytesStart, span: Span) -> Result<FdxParagraph> {
+    let attribute = |name: &str| -> Result<String> {
+        Ok(match element.try_get_attribute(name)? {
+            Some(attribute) => attribute.unescape_value()?.into_owned(),
+            None => String::new(),
+        })
+    };
+    Ok(FdxParagraph {
+        kind: attribute("Type")?,
+        alignment: attribute("Alignment")?,
+        text: String::new(),
+        span,
+    })
+}
+
+/// Turns byte offsets into spans while reading the document from start to
+/// end, counting lines only over the source between one offset and the next.
+#[derive(Debug, Default)]
+struct LineTracker {
+    offset: usize,
+    /// Line breaks before `offset`
+    line: usize,
+    line_start: usize,
+}
+
+impl LineTracker {
+    fn span(&mut self, source: &str, byte_range: Range<usize>) -> Span {
+        // Offsets only move forward while reading, but start over if not
+        if byte_range.start < self.offset {
+            *self = Self::default();
+        }
+        let skipped = &source[self.offset..byte_range.start];
+        if let Some(last) = skipped.rfind('\n') {
+            self.line += skipped.matches('\n').count();
+            self.line_start = self.offset + last + 1;
+        }
+        self.offset = byte_range.start;
+        let column = source[self.line_start..byte_range.start].chars().count() + 1;
+        Span::new(self.line + 1, column, byte_range)
+    }
+}
+
+/// Reads the title page back from the layout [`FdxRenderer`] writes: the
+/// first paragraph is the title; of the centred paragraphs below it, one
+/// ending in "by" is the credit and the one after it the author, and the
+/// rest are notes. Left-aligned paragraphs are the contact details;
+/// right-aligned ones the copyright notice, if they look like one, or the
+/// draft date.
+fn parse_title_page(paragraphs: &[FdxParagraph]) -> Option<TitlePage> {
+    let mut paragraphs = paragraphs.iter().filter(|paragraph| !paragraph.text.trim().is_empty());
+    let first = paragraphs.next()?;
+    let mut title_page = TitlePage::new(first.text.trim().to_string(), first.span.clone());
+
+    // Only the paragraph after a "by" credit is the author
+    let mut expect_author = false;
+    for paragraph in paragraphs {
+        let text = paragraph.text.trim();
+        let lower = text.to_lowercase();
+        title_page.span = title_page.span.to(&paragraph.span);
+
+        match paragraph.alignment.as_str() {
+            "Left" | "Full" => {
+                title_page.set_field("contact", text);
+            }
+            "Right" => {
+                let is_copyright = lower.starts_with("copyright") || lower.starts_with("(c)") || text.starts_with('©');
+                title_page.set_field(if is_copyright { "copyright" } else { "draft date" }, text);
+            }
+            _ if title_page.credit.is_none() && (lower == "by" || lower.ends_with(" by")) => {
+                title_page.credit = Some(text.to_string());
+                expect_author = true;
+            }
+            _ if expect_author && title_page.author.is_none() => {
+                title_page.author = Some(text.to_string());
+                expect_author = false;
+            }
+            _ => {
+                title_page.notes.push(text.to_string());
+                expect_author = false;
+            }
+        }
+    }
+    Some(title_page)
+}
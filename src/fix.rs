@@ -0,0 +1,106 @@
+use crate::diagnostic::{Diagnostic, TextEdit};
+use similar::TextDiff;
+use std::ops::Range;
+
+/// The result of applying suggested fixes to a source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixOutcome {
+    pub source: String,
+    /// Number of diagnostics whose fixes were applied.
+    pub applied: usize,
+    /// Number of fixable diagnostics left out because their edits overlapped
+    /// an earlier fix.
+    pub skipped: usize,
+}
+
+impl FixOutcome {
+    pub fn is_changed(&self) -> bool {
+        self.applied > 0
+    }
+}
+
+/// Applies the edits of every fixable suggestion in `diagnostics` to `source`.
+/// A diagnostic's edits are applied together or not at all; when they overlap
+/// the edits of an earlier diagnostic, the later one is skipped.
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> FixOutcome {
+    let mut accepted: Vec<TextEdit> = Vec::new();
+    let mut applied = 0;
+    let mut skipped = 0;
+
+    for suggestion in diagnostics.iter().filter_map(|diagnostic| diagnostic.suggestion.as_ref()) {
+        if !suggestion.is_fixable() {
+            continue;
+        }
+
+        let edits: Vec<TextEdit> = suggestion
+            .edits
+            .iter()
+            .map(|edit| TextEdit::new(expand_deletion(source, &edit.byte_range, &edit.replacement), edit.replacement.clone()))
+            .collect();
+        let valid = edits.iter().all(|edit| {
+            edit.byte_range.start <= edit.byte_range.end
+                && edit.byte_range.end <= source.len()
+                && source.is_char_boundary(edit.byte_range.start)
+                && source.is_char_boundary(edit.byte_range.end)
+        });
+        let overlaps = edits.iter().any(|edit| {
+            accepted.iter().any(|other| {
+                edit.byte_range.start < other.byte_range.end && other.byte_range.start < edit.byte_range.end
+            })
+        });
+
+        if valid && !overlaps {
+            accepted.extend(edits);
+            applied += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+
+    // Apply from the end so earlier byte offsets stay valid
+    accepted.sort_by_key(|edit| std::cmp::Reverse(edit.byte_range.start));
+    let mut fixed = source.to_string();
+    for edit in &accepted {
+        fixed.replace_range(edit.byte_range.clone(), &edit.replacement);
+    }
+
+    FixOutcome {
+        source: fixed,
+        applied,
+        skipped,
+    }
+}
+
+/// Renders the changes between `original` and `fixed` as a unified diff.
+pub fn diff(original: &str, fixed: &str, file_name: &str) -> String {
+    TextDiff::from_lines(original, fixed)
+        .unified_diff()
+        .context_radius(2)
+        .header(&format!("a/{}", file_name), &format!("b/{}", file_name))
+        .to_string()
+}
+
+/// Widens a deletion that covers whole lines to include the line break, so
+/// removing a construct does not leave a blank line behind.
+fn expand_deletion(source: &str, range: &Range<usize>, replacement: &str) -> Range<usize> {
+    if !replacement.is_empty()
+        || range.start > range.end
+        || !source.is_char_boundary(range.start)
+        || !source.is_char_boundary(range.end)
+    {
+        return range.clone();
+    }
+
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.end..].find('\n').map(|i| range.end + i);
+    let before = &source[line_start..range.start];
+    let after = &source[range.end..line_end.unwrap_or(source.len())];
+
+    if !before.trim().is_empty() || !after.trim().is_empty() {
+        return range.clone();
+    }
+    match line_end {
+        Some(end) => line_start..end + 1,
+        None => line_start..source.len(),
+    }
+}
@@ -0,0 +1,552 @@
+use crate::ast::{Character, Parenthetical, Scene, Script, ScriptElement, Span, TitlePage};
+use crate::parser::NarratorCodes;
+use crate::renderer::{RenderOptions, Renderer};
+use anyhow::Result;
+use std::io::Write;
+
+/// Words that open a scene heading when followed by a period or a space.
+const SCENE_HEADING_PREFIXES: &[&str] = &["INT./EXT", "INT/EXT", "I/E", "INT", "EXT", "EST"];
+
+/// Keys that can open a title page, matched case-insensitively. Later lines
+/// may use any key.
+const FOUNTAIN_TITLE_KEYS: &[&str] = &[
+    "title", "credit", "author", "authors", "source", "draft date", "contact", "copyright", "notes", "revision",
+];
+
+/// Parses a script written in Fountain (https://fountain.io) into the same
+/// AST as the Markdown DSL.
+///
+/// Fountain has no cast list, so the character table is built from the
+/// character cues, in order of first appearance. Action paragraphs become
+/// narration, or action when wrapped in parentheses; notes, boneyard,
+/// sections, synopses and page breaks are skipped.
+pub struct FountainParser {
+    source: String,
+    narrator_codes: NarratorCodes,
+}
+
+/// Writes a script as Fountain, the inverse of [`FountainParser`].
+pub struct FountainRenderer;
+
+/// One physical line of the source, with comments blanked out.
+struct SourceLine<'a> {
+    text: &'a str,
+    number: usize,
+    start: usize,
+}
+
+impl FountainParser {
+    pub fn new(source: String) -> Self {
+        FountainParser {
+            source,
+            narrator_codes: NarratorCodes::default(),
+        }
+    }
+
+    /// Adds character codes whose speeches are parsed as narration, in
+    /// addition to the reserved `N` code.
+    pub fn with_narrator_codes<I, S>(mut self, codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.narrator_codes.extend(codes);
+        self
+    }
+
+    pub fn parse(&self) -> Result<Script> {
+        let cleaned = strip_comments(&self.source);
+        let lines = source_lines(&self.source, &cleaned);
+
+        let mut script = Script::new();
+        let mut index = 0;
+        if let Some((title_page, next)) = parse_title_page(&lines) {
+            script.title_page = Some(title_page);
+            index = next;
+        }
+
+        let mut scene = Scene::new(None, Span::default());
+        while let Some(line) = lines.get(index) {
+            let text = line.text.trim();
+            let isolated = (index == 0 || lines[index - 1].is_blank())
+                && lines.get(index + 1).is_none_or(SourceLine::is_blank);
+
+            if text.is_empty() || is_skipped(text) {
+                index += 1;
+                continue;
+            }
+
+            if let Some(location) = scene_heading(text, isolated) {
+                let previous = std::mem::replace(&mut scene, Scene::new(Some(location), line.span()));
+                if !previous.elements.is_empty() || previous.location.is_some() {
+                    script.scenes.push(previous);
+                }
+                index += 1;
+                continue;
+            }
+
+            if let Some(transition) = transition(text, isolated) {
+                scene.push(ScriptElement::Transition {
+                    text: transition.to_string(),
+                    span: line.span(),
+                });
+                index += 1;
+                continue;
+            }
+
+            let follows_blank = index == 0 || lines[index - 1].is_blank();
+            let has_speech = lines.get(index + 1).is_some_and(|next| !next.is_blank());
+            if let Some(code) = cue(text, follows_blank).filter(|_| has_speech) {
+                let (element, next) = self.parse_speech(&lines, index, code);
+                if let ScriptElement::Dialogue { speaker, .. } = &element
+                    && !script.characters.contains(speaker)
+                {
+                    let name = character_name(speaker);
+                    script.characters.insert(Character::new(speaker.clone(), name, line.span()));
+                }
+                scene.push(element);
+                index = next;
+                continue;
+            }
+
+            let (element, next) = parse_action(&lines, index);
+            scene.push(element);
+            index = next;
+        }
+
+        if !scene.elements.is_empty() || scene.location.is_some() {
+            script.scenes.push(scene);
+        }
+
+        Ok(script)
+    }
+
+    /// Reads the speech under the cue at `index`, up to the next blank line.
+    /// A line of two spaces starts a new paragraph; lines in parentheses are
+    /// parentheticals. Returns the element and the index after the speech.
+    fn parse_speech(&self, lines: &[SourceLine], index: usize, speaker: String) -> (ScriptElement, usize) {
+        let mut span = lines[index].span();
+        let mut text = String::new();
+        let mut actions = Vec::new();
+        let mut separator = " ";
+        let mut index = index + 1;
+
+        while let Some(line) = lines.get(index) {
+            if line.is_blank() {
+                if !line.is_paragraph_break() {
+                    break;
+                }
+                separator = "\n";
+                index += 1;
+                continue;
+            }
+
+            let content = line.text.trim();
+            span = span.to(&line.span());
+            if let Some(inner) = content.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+                // Like inline parentheticals in the DSL, it sits just before
+                // the next piece of text
+                actions.push(Parenthetical {
+                    text: inner.trim().to_string(),
+                    offset: if text.is_empty() { 0 } else { text.len() + 1 },
+                    span: line.span(),
+                });
+            } else {
+                if !text.is_empty() {
+                    text.push_str(separator);
+                }
+                text.push_str(content.strip_prefix('~').unwrap_or(content).trim());
+                separator = " ";
+            }
+            index += 1;
+        }
+
+        (self.narrator_codes.speech(speaker, text, actions, span), index)
+    }
+}
+
+impl SourceLine<'_> {
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    /// Two or more spaces on their own keep a speech going across a blank line.
+    fn is_paragraph_break(&self) -> bool {
+        self.is_blank() && self.text.trim_end_matches('\r').len() >= 2
+    }
+
+    fn span(&self) -> Span {
+        let indent = self.text.len() - self.text.trim_start().len();
+        let end = self.text.trim_end().len().max(indent);
+        Span::new(
+            self.number,
+            self.text[..indent].chars().count() + 1,
+            self.start + indent..self.start + end,
+        )
+    }
+}
+
+/// Replaces `/* boneyard */` and `[[notes]]` with spaces, keeping line
+/// breaks and byte offsets intact so spans still point into the source.
+fn strip_comments(source: &str) -> String {
+    let mut cleaned = String::with_capacity(source.len());
+    let mut closing: Option<&str> = None;
+    let mut rest = source;
+
+    while let Some(c) = rest.chars().next() {
+        let skipped = match closing {
+            Some(end) if rest.starts_with(end) => {
+                closing = None;
+                end.len()
+            }
+            Some(_) => 0,
+            None if rest.starts_with("/*") => {
+                closing = Some("*/");
+                2
+            }
+            None if rest.starts_with("[[") => {
+                closing = Some("]]");
+                2
+            }
+            None => {
+                cleaned.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+        };
+
+        if skipped > 0 {
+            cleaned.push_str(&" ".repeat(skipped));
+            rest = &rest[skipped..];
+        } else {
+            cleaned.push(if c == '\n' { '\n' } else { ' ' });
+            cleaned.push_str(&" ".repeat(c.len_utf8() - 1));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    cleaned
+}
+
+/// Splits `cleaned` into lines. Lines that held nothing but comments in
+/// `source` are dropped, so they neither end nor split a block.
+fn source_lines<'a>(source: &str, cleaned: &'a str) -> Vec<SourceLine<'a>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (index, text) in cleaned.split('\n').enumerate() {
+        let original = &source[start..start + text.len()];
+        if text.trim().is_empty() && !original.trim().is_empty() {
+            start += text.len() + 1;
+            continue;
+        }
+        lines.push(SourceLine { text, number: index + 1, start });
+        start += text.len() + 1;
+    }
+    lines
+}
+
+/// Reads the `Key: value` block at the start of the file, if there is one.
+/// Returns the title page and the index of the first line after it.
+fn parse_title_page(lines: &[SourceLine]) -> Option<(TitlePage, usize)> {
+    let first = lines.first()?;
+    let (key, _) = title_key(first.text)?;
+    if !FOUNTAIN_TITLE_KEYS.contains(&key.to_lowercase().as_str()) {
+        return None;
+    }
+
+    let mut fields: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut span = first.span();
+    let mut index = 0;
+    while let Some(line) = lines.get(index) {
+        if line.is_blank() {
+            break;
+        }
+        if line.text.starts_with("   ") || line.text.starts_with('\t') {
+            // Indented lines continue the value of the previous key
+            if let Some((_, values)) = fields.last_mut() {
+                values.push(line.text.trim());
+            }
+        } else if let Some((key, value)) = title_key(line.text) {
+            fields.push((key, if value.is_empty() { vec![] } else { vec![value] }));
+        } else {
+            break;
+        }
+        span = span.to(&line.span());
+        index += 1;
+    }
+
+    let mut title_page = TitlePage::new(String::new(), span);
+    for (key, values) in fields {
+        match key.to_lowercase().as_str() {
+            "title" => title_page.title = values.join(" "),
+            "notes" => title_page.notes.extend(values.iter().map(|value| value.to_string())),
+            "credit" | "author" | "authors" | "draft date" | "contact" | "copyright" => {
+                for value in values {
+                    title_page.set_field(key, value);
+                }
+            }
+            // Keys the AST has no field for are kept as notes
+            _ => title_page.notes.push(format!("{}: {}", key, values.join(" "))),
+        }
+    }
+    Some((title_page, index))
+}
+
+/// A title page `Key: value` line; keys are made of letters and spaces.
+fn title_key(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let valid = !key.is_empty()
+        && !key.starts_with(' ')
+        && key.chars().all(|c| c.is_alphabetic() || c == ' ');
+    valid.then(|| (key.trim(), value.trim()))
+}
+
+/// Sections, synopses and page breaks, which have no place in the AST.
+fn is_skipped(text: &str) -> bool {
+    text.starts_with('#') || text.starts_with('=')
+}
+
+fn has_heading_prefix(text: &str) -> bool {
+    let upper = text.to_uppercase();
+    SCENE_HEADING_PREFIXES.iter().any(|prefix| {
+        upper
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.') || rest.starts_with(' '))
+    })
+}
+
+/// The location of a scene heading, without any `#1#` scene number. Headings
+/// need a blank line on either side unless forced with a leading period.
+fn scene_heading(text: &str, isolated: bool) -> Option<String> {
+    let heading = match text.strip_prefix('.') {
+        Some(forced) if !forced.starts_with('.') => forced,
+        _ if isolated && has_heading_prefix(text) => text,
+        _ => return None,
+    };
+    let heading = match heading.trim_end().strip_suffix('#').and_then(|rest| rest.rsplit_once('#')) {
+        Some((location, _number)) => location,
+        None => heading,
+    };
+    Some(heading.trim().to_string())
+}
+
+/// An uppercase line ending in `TO:`, or any line forced with `>` that is not
+/// `>centered<` text.
+fn transition(text: &str, isolated: bool) -> Option<&str> {
+    if let Some(forced) = text.strip_prefix('>') {
+        return (!forced.ends_with('<')).then(|| forced.trim());
+    }
+    (isolated && is_uppercase(text) && text.ends_with("TO:")).then_some(text)
+}
+
+/// The character code of a cue line: an uppercase name after a blank line,
+/// or any name forced with `@`. Extensions such as `(V.O.)` and the `^` of
+/// dual dialogue are dropped.
+fn cue(text: &str, follows_blank: bool) -> Option<String> {
+    let (name, forced) = match text.strip_prefix('@') {
+        Some(name) => (name, true),
+        None if follows_blank && !text.starts_with('!') => (text, false),
+        None => return None,
+    };
+    let name = name.trim_end_matches('^').trim_end();
+    let code = name.split_once('(').map_or(name, |(code, _extension)| code).trim();
+
+    let valid = !code.is_empty() && (forced || is_uppercase(code));
+    valid.then(|| code.to_string())
+}
+
+/// Whether `text` has letters and all of them are uppercase.
+fn is_uppercase(text: &str) -> bool {
+    text.chars().any(char::is_alphabetic) && !text.chars().any(char::is_lowercase)
+}
+
+/// Reads an action paragraph, up to the next blank line, as narration, or as
+/// action if it is wrapped in parentheses.
+fn parse_action(lines: &[SourceLine], index: usize) -> (ScriptElement, usize) {
+    let mut span = lines[index].span();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let forced = lines[index].text.trim().starts_with('!');
+    let mut index = index;
+
+    while let Some(line) = lines.get(index).filter(|line| !line.is_blank()) {
+        let text = line.text.trim();
+        let text = text.strip_prefix('!').unwrap_or(text);
+        let text = text.strip_prefix('~').unwrap_or(text);
+        // `>centered<` text
+        let text = match text.strip_prefix('>').and_then(|rest| rest.strip_suffix('<')) {
+            Some(centered) => centered.trim(),
+            None => text.trim(),
+        };
+        paragraph.push(text);
+        span = span.to(&line.span());
+        index += 1;
+    }
+
+    let text = paragraph.join(" ");
+    let element = match text.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        Some(inner) if !forced => ScriptElement::Action {
+            text: inner.trim().to_string(),
+            span,
+        },
+        _ => ScriptElement::Narration { text, voice: None, span },
+    };
+    (element, index)
+}
+
+/// A readable name for a character known only by its cue, e.g. `Mrs. Smith`
+/// for `MRS. SMITH`.
+pub(crate) fn character_name(code: &str) -> String {
+    code.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Default for FountainRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FountainRenderer {
+    pub fn new() -> Self {
+        FountainRenderer
+    }
+}
+
+impl Renderer for FountainRenderer {
+    fn name(&self) -> &'static str {
+        "fountain"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "fountain"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, _options: &RenderOptions) -> Result<()> {
+        // Everything is written as blocks separated by a blank line
+        let mut blocks = Vec::new();
+
+        if let Some(title_page) = &script.title_page {
+            let block = title_page_block(title_page);
+            if !block.is_empty() {
+                blocks.push(block);
+            }
+        }
+
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location {
+                let forced = scene_heading(location, true).as_deref() != Some(location.as_str());
+                blocks.push(if forced { format!(".{}", location) } else { location.clone() });
+            }
+
+            for element in &scene.elements {
+                match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        blocks.push(speech_block(speaker, text, actions));
+                    }
+                    ScriptElement::Narration { text, voice: Some(voice), .. } => {
+                        blocks.push(speech_block(voice, text, &[]));
+                    }
+                    ScriptElement::Narration { text, voice: None, .. } => {
+                        blocks.extend(
+                            text.split('\n')
+                                .map(str::trim)
+                                .filter(|paragraph| !paragraph.is_empty())
+                                .map(action_block),
+                        );
+                    }
+                    ScriptElement::Action { text, .. } => {
+                        blocks.push(format!("({})", text.replace('\n', " ")));
+                    }
+                    ScriptElement::Transition { text, .. } => {
+                        let forced = transition(text, true) != Some(text.as_str());
+                        blocks.push(if forced { format!("> {}", text) } else { text.clone() });
+                    }
+                }
+            }
+        }
+
+        if !blocks.is_empty() {
+            writeln!(out, "{}", blocks.join("\n\n"))?;
+        }
+        Ok(())
+    }
+}
+
+fn title_page_block(title_page: &TitlePage) -> String {
+    let mut lines = Vec::new();
+    let mut field = |key: &str, values: Vec<&str>| match values.as_slice() {
+        [] => {}
+        [value] => lines.push(format!("{}: {}", key, value)),
+        values => {
+            lines.push(format!("{}:", key));
+            lines.extend(values.iter().map(|value| format!("    {}", value)));
+        }
+    };
+
+    if !title_page.title.is_empty() {
+        field("Title", vec![title_page.title.as_str()]);
+    }
+    for (key, value) in [
+        ("Credit", &title_page.credit),
+        ("Author", &title_page.author),
+        ("Draft date", &title_page.draft_date),
+        ("Contact", &title_page.contact),
+        ("Copyright", &title_page.copyright),
+    ] {
+        if let Some(value) = value {
+            field(key, value.lines().collect());
+        }
+    }
+    field("Notes", title_page.notes.iter().map(String::as_str).collect());
+
+    lines.join("\n")
+}
+
+/// A character cue followed by the speech, with each parenthetical on its own
+/// line where it occurred and paragraphs kept apart by a line of two spaces.
+fn speech_block(speaker: &str, text: &str, actions: &[Parenthetical]) -> String {
+    fn push_speech(lines: &mut Vec<String>, speech: &str) {
+        for (index, paragraph) in speech.split('\n').enumerate() {
+            if index > 0 {
+                lines.push("  ".to_string());
+            }
+            if !paragraph.trim().is_empty() {
+                lines.push(paragraph.trim().to_string());
+            }
+        }
+    }
+
+    let forced = cue(speaker, true).as_deref() != Some(speaker) || has_heading_prefix(speaker);
+    let mut lines = vec![if forced { format!("@{}", speaker) } else { speaker.to_string() }];
+    let mut position = 0;
+    for action in actions {
+        let offset = action.position_in(text, position);
+        push_speech(&mut lines, &text[position..offset]);
+        lines.push(format!("({})", action.text));
+        position = offset;
+    }
+    push_speech(&mut lines, &text[position..]);
+    lines.join("\n")
+}
+
+/// An action paragraph, forced with `!` if it would otherwise be read as
+/// something else.
+fn action_block(text: &str) -> String {
+    let ambiguous = text.starts_with(['.', '!', '@', '~', '>', '=', '#', '('])
+        || text.starts_with("[[")
+        || text.starts_with("/*")
+        || scene_heading(text, true).is_some()
+        || transition(text, true).is_some();
+    if ambiguous {
+        format!("!{}", text)
+    } else {
+        text.to_string()
+    }
+}
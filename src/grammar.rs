@@ -0,0 +1,388 @@
+use crate::ast::{Parenthetical, Span, SuppressionScope, CHARACTER_ATTRIBUTE_KEYS, TITLE_PAGE_KEYS};
+use crate::lexer::Token;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take, take_till, take_till1, take_until, take_while};
+use nom::character::complete::{char, multispace0, multispace1};
+use nom::combinator::{all_consuming, eof, map, opt, rest, value, verify};
+use nom::error::{Error, ErrorKind};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
+use nom::IResult;
+use nom_locate::LocatedSpan;
+use serde::{Deserialize, Serialize};
+
+/// Source text annotated with its byte offset, line and column, so every
+/// parser knows where its input came from.
+pub type Input<'a> = LocatedSpan<&'a str>;
+
+/// One physical line of the source: its leading whitespace and its content
+/// with surrounding whitespace removed.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub indent: Input<'a>,
+    pub content: Input<'a>,
+}
+
+/// Which characters a character code may contain, besides the uppercase
+/// letter it must start with. Configured in the `[character-codes]` table of
+/// `script-parser.toml`; everything is allowed by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodeGrammar {
+    /// `COP2`
+    pub digits: bool,
+    /// `DR_LEE`
+    pub underscores: bool,
+    /// `MRS SMITH`
+    pub spaces: bool,
+    /// `MRS. SMITH`
+    pub periods: bool,
+    /// Uppercase letters outside ASCII, as in `JOSÉ`
+    pub unicode: bool,
+}
+
+impl Default for CodeGrammar {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+impl CodeGrammar {
+    /// Codes made of ASCII uppercase letters only, e.g. `ALICE`.
+    pub fn strict() -> Self {
+        CodeGrammar {
+            digits: false,
+            underscores: false,
+            spaces: false,
+            periods: false,
+            unicode: false,
+        }
+    }
+
+    pub fn lenient() -> Self {
+        CodeGrammar {
+            digits: true,
+            underscores: true,
+            spaces: true,
+            periods: true,
+            unicode: true,
+        }
+    }
+
+    fn is_letter(&self, c: char) -> bool {
+        if self.unicode {
+            c.is_alphabetic() && c.is_uppercase()
+        } else {
+            c.is_ascii_uppercase()
+        }
+    }
+
+    fn is_code_char(&self, c: char) -> bool {
+        self.is_letter(c)
+            || (self.digits && c.is_ascii_digit())
+            || (self.underscores && c == '_')
+            || (self.spaces && c == ' ')
+            || (self.periods && c == '.')
+    }
+
+    /// Whether `code` is a valid character code: an uppercase letter followed
+    /// by allowed characters, without surrounding spaces.
+    pub fn is_valid(&self, code: &str) -> bool {
+        let mut chars = code.chars();
+        chars.next().is_some_and(|c| self.is_letter(c))
+            && chars.all(|c| self.is_code_char(c))
+            && !code.ends_with(' ')
+    }
+
+    /// Human-readable summary of the allowed characters, for diagnostics.
+    pub fn describe(&self) -> String {
+        let mut allowed = vec!["uppercase letters"];
+        if self.digits {
+            allowed.push("digits");
+        }
+        if self.underscores {
+            allowed.push("underscores");
+        }
+        if self.spaces {
+            allowed.push("spaces");
+        }
+        if self.periods {
+            allowed.push("periods");
+        }
+        match allowed.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            _ => allowed.join(""),
+        }
+    }
+}
+
+
+impl Line<'_> {
+    pub fn is_blank(&self) -> bool {
+        self.content.fragment().is_empty()
+    }
+
+    pub fn is_indented(&self) -> bool {
+        !self.indent.fragment().is_empty()
+    }
+
+    /// The span of the line's content, or an empty span at the start of the
+    /// line if it is blank.
+    pub fn span(&self) -> Span {
+        if self.is_blank() {
+            let offset = self.indent.location_offset();
+            Span::new(self.indent.location_line() as usize, 1, offset..offset)
+        } else {
+            span(&self.content)
+        }
+    }
+}
+
+/// The source location covered by `input`.
+pub fn span(input: &Input) -> Span {
+    let start = input.location_offset();
+    Span::new(
+        input.location_line() as usize,
+        input.get_utf8_column(),
+        start..start + input.fragment().len(),
+    )
+}
+
+/// Splits off the next line of the source, consuming its line break. A
+/// trailing `\r` is treated as part of the line break.
+pub fn line(input: Input) -> IResult<Input, Line> {
+    let (input, raw) = take_till(|c| c == '\n')(input)?;
+    let (input, _) = opt(char('\n'))(input)?;
+
+    let (content, indent) = take_while(char::is_whitespace)(raw)?;
+    let (_, content) = take(content.fragment().trim_end().chars().count())(content)?;
+    Ok((input, Line { indent, content }))
+}
+
+/// `<!-- script-parser: disable[-next-line] [rule-id, ...] -->`
+pub fn suppression(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    let (input, body) = all_consuming(delimited(tag("<!--"), take_until("-->"), tag("-->")))(input)?;
+    let (_, (scope, rules)) = all_consuming(preceded(
+        tuple((multispace0, tag("script-parser:"), multispace0)),
+        pair(
+            alt((
+                value(SuppressionScope::NextLine, tag("disable-next-line")),
+                value(SuppressionScope::File, tag("disable")),
+            )),
+            rule_list,
+        ),
+    ))(body)?;
+    Ok((input, Token::Suppression { rules, scope, span: location }))
+}
+
+/// Rule IDs separated by whitespace or commas, possibly none.
+fn rule_list(input: Input) -> IResult<Input, Vec<String>> {
+    let is_separator = |c: char| c == ',' || c.is_whitespace();
+    let (input, _) = alt((multispace1, eof))(input)?;
+    let (input, ids) = many0(preceded(take_while(is_separator), take_till1(is_separator)))(input)?;
+    let (input, _) = take_while(is_separator)(input)?;
+    Ok((input, ids.iter().map(|id| id.fragment().to_string()).collect()))
+}
+
+/// `## Name`
+pub fn section_header(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    map(preceded(tag("## "), rest), move |name: Input| Token::SectionHeader {
+        name: name.fragment().trim().to_string(),
+        span: location.clone(),
+    })(input)
+}
+
+/// `# Title`, or a bare `#` for a script whose title is still empty
+pub fn title_heading(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    let title = alt((preceded(tag("# "), rest), preceded(all_consuming(tag("#")), rest)));
+    map(title, move |title: Input| Token::TitleHeading {
+        title: title.fragment().trim().to_string(),
+        span: location.clone(),
+    })(input)
+}
+
+/// A `Key: value` line in the title section with one of [`TITLE_PAGE_KEYS`],
+/// or any other line as free text.
+pub fn title_line(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    alt((
+        map(field(TITLE_PAGE_KEYS), |(key, value)| Token::TitleField {
+            key,
+            value,
+            span: location.clone(),
+        }),
+        map(rest, |text: Input| Token::TitleText {
+            text: text.fragment().to_string(),
+            span: location.clone(),
+        }),
+    ))(input)
+}
+
+/// An indented `key: value` line below a character definition.
+pub fn character_attr(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    map(field(CHARACTER_ATTRIBUTE_KEYS), move |(key, value)| Token::CharacterAttr {
+        key,
+        value,
+        span: location.clone(),
+    })(input)
+}
+
+/// `CODE: Name`. Any code is accepted here; `invalid-character-code` checks
+/// it against the grammar so malformed definitions are reported.
+pub fn character_def(input: Input) -> IResult<Input, Token> {
+    let location = span(&input);
+    map(coded_line, move |(code, name)| Token::CharacterDef {
+        code: code.fragment().trim_end().to_string(),
+        name: name.fragment().to_string(),
+        span: location.clone(),
+    })(input)
+}
+
+/// A line in the script section: a `[Location]` header, an `(action)`, a
+/// `> TRANSITION`, a `CODE: dialogue` line, or narration. A leading `\`
+/// makes the rest of the line narration, whatever it looks like.
+pub fn script_line(grammar: CodeGrammar) -> impl FnMut(Input) -> IResult<Input, Token> {
+    move |input: Input| {
+        let location = span(&input);
+        alt((
+            map(preceded(char('\\'), rest), |text: Input| Token::NarrationLine {
+                text: text.fragment().to_string(),
+                span: location.clone(),
+            }),
+            map(enclosed('[', ']'), |name: Input| Token::LocationHeader {
+                name: name.fragment().to_string(),
+                span: location.clone(),
+            }),
+            map(enclosed('(', ')'), |text: Input| Token::ActionText {
+                text: text.fragment().to_string(),
+                span: location.clone(),
+            }),
+            map(preceded(char('>'), rest), |text: Input| Token::TransitionLine {
+                text: text.fragment().trim().to_string(),
+                span: location.clone(),
+            }),
+            map(
+                verify(coded_line, |(code, _): &(Input, Input)| grammar.is_valid(code.fragment().trim_end())),
+                |(speaker, text)| {
+                    let start = text.location_offset() - input.location_offset();
+                    let (text, parentheticals) = split_parentheticals(input.fragment(), start, &location);
+                    Token::DialogueLine {
+                        speaker: speaker.fragment().trim_end().to_string(),
+                        text,
+                        parentheticals,
+                        span: location.clone(),
+                    }
+                },
+            ),
+            map(rest, |text: Input| Token::NarrationLine {
+                text: text.fragment().to_string(),
+                span: location.clone(),
+            }),
+        ))(input)
+    }
+}
+
+/// A `key: value` line whose key is one of `keys`, matched case-insensitively.
+/// Returns the trimmed key and value.
+fn field<'a>(keys: &'static [&'static str]) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, (String, String)> {
+    map(
+        verify(
+            separated_pair(take_till(|c| c == ':'), char(':'), rest),
+            move |(key, _): &(Input, Input)| keys.contains(&key.fragment().trim().to_lowercase().as_str()),
+        ),
+        |(key, value): (Input, Input)| (key.fragment().trim().to_string(), value.fragment().trim().to_string()),
+    )
+}
+
+/// `CODE: text`, split at the first colon. The text must not be empty.
+fn coded_line(input: Input) -> IResult<Input, (Input, Input)> {
+    separated_pair(
+        take_till(|c| c == ':'),
+        char(':'),
+        preceded(multispace0, verify(rest, |text: &Input| !text.fragment().is_empty())),
+    )(input)
+}
+
+/// A whole line wrapped in `open` and `close`, returning what is inside.
+fn enclosed<'a>(open: char, close: char) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, Input<'a>> {
+    move |input: Input<'a>| {
+        let (inner, _) = char(open)(input)?;
+        let Some(body) = inner.fragment().strip_suffix(close) else {
+            return Err(nom::Err::Error(Error::new(inner, ErrorKind::Char)));
+        };
+        let (after, body) = take(body.chars().count())(inner)?;
+        let (after, _) = all_consuming(char(close))(after)?;
+        Ok((after, body))
+    }
+}
+
+/// A run of dialogue: either a `(...)` parenthetical or text up to the next
+/// one. An unclosed `(` is kept as text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Piece<'a> {
+    Text(&'a str),
+    Parenthetical(&'a str),
+}
+
+fn piece(input: &str) -> IResult<&str, Piece<'_>> {
+    alt((
+        map(
+            delimited(char('('), take_until(")"), char(')')),
+            Piece::Parenthetical,
+        ),
+        map(take_till1(|c| c == '('), Piece::Text),
+        map(verify(rest, |text: &str| !text.is_empty()), Piece::Text),
+    ))(input)
+}
+
+/// Removes inline `(...)` parentheticals from the dialogue text that starts
+/// at byte `start` of `line`, recording where each one occurred. `span` is the
+/// source location of `line`.
+pub(crate) fn split_parentheticals(line: &str, start: usize, span: &Span) -> (String, Vec<Parenthetical>) {
+    fn push_piece(text: &mut String, piece: &str) {
+        let piece = piece.trim();
+        if !piece.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(piece);
+        }
+    }
+
+    let mut text = String::new();
+    let mut parentheticals = Vec::new();
+    let mut input = &line[start..];
+
+    while let Ok((remaining, next)) = piece(input) {
+        let position = line.len() - input.len();
+        match next {
+            Piece::Text(piece) => push_piece(&mut text, piece),
+            Piece::Parenthetical(inner) => {
+                // The parenthetical sits just before the next piece of text
+                let offset = if text.is_empty() { 0 } else { text.len() + 1 };
+                let length = input.len() - remaining.len();
+                let source_start = span.byte_range.start + position;
+                parentheticals.push(Parenthetical {
+                    text: inner.trim().to_string(),
+                    offset,
+                    span: Span::new(
+                        span.line,
+                        span.column + line[..position].chars().count(),
+                        source_start..source_start + length,
+                    ),
+                });
+            }
+        }
+        input = remaining;
+    }
+
+    for parenthetical in &mut parentheticals {
+        parenthetical.offset = parenthetical.offset.min(text.len());
+    }
+
+    (text, parentheticals)
+}
@@ -0,0 +1,206 @@
+use crate::ast::{Script, ScriptElement, TitlePage};
+use crate::layout::LayoutProfile;
+use crate::renderer::{dialogue_parts, DialoguePart, RenderOptions, Renderer};
+use anyhow::Result;
+use std::io::Write;
+
+/// Renders a script as a standalone HTML page: semantic markup for scene
+/// headings, character cues, dialogue, parentheticals and action, with an
+/// embedded stylesheet that follows the screenplay layout on screen and in
+/// print.
+pub struct HtmlRenderer;
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        HtmlRenderer
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, options: &RenderOptions) -> Result<()> {
+        let title = script.title_page.as_ref().map_or("Script", |page| page.title.as_str());
+
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>{}</title>", escape(title))?;
+        writeln!(out, "<style>\n{}</style>", stylesheet(&options.profile))?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<article class=\"screenplay\">")?;
+
+        if let Some(title_page) = &script.title_page {
+            write_title_page(out, title_page)?;
+        }
+
+        if !script.characters.is_empty() {
+            writeln!(out, "<section class=\"characters\">")?;
+            writeln!(out, "<h2>Characters</h2>")?;
+            writeln!(out, "<dl>")?;
+            for character in &script.characters {
+                writeln!(out, "<dt>{}</dt>", escape(&character.code))?;
+                writeln!(out, "<dd>{}</dd>", escape(&character.name))?;
+            }
+            writeln!(out, "</dl>")?;
+            writeln!(out, "</section>")?;
+        }
+
+        for scene in &script.scenes {
+            writeln!(out, "<section class=\"scene\">")?;
+            if let Some(location) = &scene.location {
+                writeln!(out, "<h2 class=\"scene-heading\">{}</h2>", escape(&location.to_uppercase()))?;
+            }
+
+            for element in &scene.elements {
+                match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        writeln!(out, "<div class=\"dialogue\">")?;
+                        writeln!(out, "<p class=\"character\">{}</p>", escape(&speaker.to_uppercase()))?;
+                        for part in dialogue_parts(text, actions) {
+                            match part {
+                                DialoguePart::Speech(speech) => write_paragraphs(out, "speech", speech)?,
+                                DialoguePart::Parenthetical(wryly) => {
+                                    writeln!(out, "<p class=\"parenthetical\">({})</p>", escape(wryly))?;
+                                }
+                            }
+                        }
+                        writeln!(out, "</div>")?;
+                    }
+                    ScriptElement::Narration { text, .. } => write_paragraphs(out, "narration", text)?,
+                    ScriptElement::Action { text, .. } => write_paragraphs(out, "action", &format!("({})", text))?,
+                    ScriptElement::Transition { text, .. } => {
+                        writeln!(out, "<p class=\"transition\">{}</p>", escape(&text.to_uppercase()))?;
+                    }
+                }
+            }
+            writeln!(out, "</section>")?;
+        }
+
+        writeln!(out, "</article>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+        Ok(())
+    }
+}
+
+fn write_title_page(out: &mut dyn Write, title_page: &TitlePage) -> Result<()> {
+    writeln!(out, "<header class=\"title-page\">")?;
+    writeln!(out, "<h1>{}</h1>", escape(&title_page.title.to_uppercase()))?;
+
+    for (class, value) in [("credit", &title_page.credit), ("author", &title_page.author)] {
+        if let Some(value) = value {
+            writeln!(out, "<p class=\"{}\">{}</p>", class, escape_lines(value))?;
+        }
+    }
+    for note in &title_page.notes {
+        writeln!(out, "<p class=\"note\">{}</p>", escape(note))?;
+    }
+    if let Some(contact) = &title_page.contact {
+        writeln!(out, "<address class=\"contact\">{}</address>", escape_lines(contact))?;
+    }
+    for (class, value) in [("draft-date", &title_page.draft_date), ("copyright", &title_page.copyright)] {
+        if let Some(value) = value {
+            writeln!(out, "<p class=\"{}\">{}</p>", class, escape_lines(value))?;
+        }
+    }
+
+    writeln!(out, "</header>")?;
+    Ok(())
+}
+
+/// Writes one `<p>` per paragraph of `text`; paragraphs are separated by
+/// newlines in the AST.
+fn write_paragraphs(out: &mut dyn Write, class: &str, text: &str) -> Result<()> {
+    for paragraph in text.split('\n') {
+        writeln!(out, "<p class=\"{}\">{}</p>", class, escape(paragraph))?;
+    }
+    Ok(())
+}
+
+/// Stylesheet mimicking the screenplay page of `profile`: Courier throughout,
+/// with indents and column widths taken from the layout so the HTML lines up
+/// with the PDF.
+fn stylesheet(profile: &LayoutProfile) -> String {
+    let text_width = profile.page_width - profile.left_margin - profile.right_margin;
+    let indent = |x: f32| x - profile.left_margin;
+    let columns = &profile.columns;
+
+    format!(
+        r#"@page {{ size: {page_width:.1}mm {page_height:.1}mm; margin: {top:.1}mm {right:.1}mm {top:.1}mm {left:.1}mm; }}
+body {{ margin: 0; background: #f4f4f4; }}
+.screenplay {{ font-family: "Courier Prime", "Courier New", Courier, monospace; font-size: {font_size}pt; line-height: 1; width: {text_width:.1}mm; margin: 2em auto; padding: {top:.1}mm {right:.1}mm {top:.1}mm {left:.1}mm; background: #fff; box-shadow: 0 0 4px rgba(0, 0, 0, 0.2); }}
+.screenplay p, .screenplay h1, .screenplay h2, .screenplay dl {{ margin: 0 0 1em; font-size: 1em; font-weight: normal; }}
+.title-page {{ text-align: center; padding-top: 30vh; min-height: 60vh; break-after: page; }}
+.title-page h1 {{ margin-bottom: 2em; }}
+.title-page .contact {{ text-align: left; font-style: normal; margin-top: 6em; }}
+.title-page .draft-date, .title-page .copyright {{ text-align: right; }}
+.characters h2 {{ text-transform: uppercase; }}
+.characters dl {{ margin-left: {dialogue:.1}mm; }}
+.characters dt {{ float: left; clear: left; }}
+.characters dt::after {{ content: ": "; white-space: pre; }}
+.characters dd {{ margin: 0; }}
+.scene-heading {{ text-transform: uppercase; break-after: avoid; }}
+.narration, .action {{ max-width: {narration}ch; }}
+.transition {{ text-align: right; break-before: avoid; }}
+.dialogue {{ margin-bottom: 1em; }}
+.dialogue p {{ margin-bottom: 0; }}
+.character {{ margin-left: {character:.1}mm; break-after: avoid; }}
+.speech {{ margin-left: {dialogue:.1}mm; max-width: {dialogue_width}ch; }}
+.parenthetical {{ margin-left: {parenthetical:.1}mm; max-width: {parenthetical_width}ch; }}
+@media print {{
+  body {{ background: none; }}
+  .screenplay {{ width: auto; margin: 0; padding: 0; box-shadow: none; }}
+  .dialogue {{ break-inside: avoid; }}
+}}
+"#,
+        page_width = profile.page_width,
+        page_height = profile.page_height,
+        top = profile.top_margin,
+        right = profile.right_margin,
+        left = profile.left_margin,
+        font_size = profile.font_size,
+        text_width = text_width,
+        narration = columns.narration,
+        character = indent(profile.character_indent),
+        dialogue = indent(profile.dialogue_indent),
+        dialogue_width = columns.dialogue,
+        parenthetical = indent(profile.parenthetical_indent),
+        parenthetical_width = columns.parenthetical,
+    )
+}
+
+/// Escapes the characters that are significant in HTML text and attributes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a multi-line metadata value, keeping its line breaks.
+fn escape_lines(text: &str) -> String {
+    text.lines().map(escape).collect::<Vec<_>>().join("<br>\n")
+}
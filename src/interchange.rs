@@ -0,0 +1,91 @@
+use crate::ast::{Script, ScriptElement};
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A data format the AST can be exchanged in, so other tools can generate or
+/// post-process scripts without parsing the Markdown themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AstFormat {
+    Json,
+    Yaml,
+}
+
+impl FromStr for AstFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(AstFormat::Json),
+            "yaml" => Ok(AstFormat::Yaml),
+            _ => Err(anyhow!("Unknown AST format '{}'", s)),
+        }
+    }
+}
+
+impl AstFormat {
+    /// The format of a file holding a serialized AST, told apart by its
+    /// extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?;
+        if extension.eq_ignore_ascii_case("json") {
+            Some(AstFormat::Json)
+        } else if extension.eq_ignore_ascii_case("yaml") || extension.eq_ignore_ascii_case("yml") {
+            Some(AstFormat::Yaml)
+        } else {
+            None
+        }
+    }
+
+    pub fn serialize(&self, script: &Script) -> Result<String> {
+        match self {
+            AstFormat::Json => Ok(serde_json::to_string_pretty(script)? + "\n"),
+            AstFormat::Yaml => Ok(serde_yaml::to_string(script)?),
+        }
+    }
+
+    /// Reads a script back, checking that parenthetical offsets point into
+    /// their dialogue text, since a generated AST may get them wrong.
+    pub fn deserialize(&self, source: &str) -> Result<Script> {
+        let script: Script = match self {
+            AstFormat::Json => serde_json::from_str(source).context("Invalid JSON AST")?,
+            AstFormat::Yaml => serde_yaml::from_str(source).context("Invalid YAML AST")?,
+        };
+        check_offsets(&script)?;
+        Ok(script)
+    }
+}
+
+/// Fails unless every parenthetical offset is within its dialogue text, on a
+/// character boundary, and not before the offset of the one preceding it.
+fn check_offsets(script: &Script) -> Result<()> {
+    for (number, scene) in script.scenes.iter().enumerate() {
+        for element in &scene.elements {
+            let ScriptElement::Dialogue { speaker, text, actions, .. } = element else {
+                continue;
+            };
+            let mut previous = 0;
+            for action in actions {
+                let problem = if action.offset > text.len() {
+                    "is past the end of the text"
+                } else if !text.is_char_boundary(action.offset) {
+                    "is inside a character"
+                } else if action.offset < previous {
+                    "is before the parenthetical preceding it"
+                } else {
+                    previous = action.offset;
+                    continue;
+                };
+                return Err(anyhow!(
+                    "Invalid AST: offset {} of parenthetical '{}' in {}'s dialogue in scene {} {}",
+                    action.offset,
+                    action.text,
+                    speaker,
+                    number + 1,
+                    problem
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use crate::ast::{Script, ScriptElement, TitlePage};
+use crate::layout::LayoutProfile;
+use crate::renderer::{dialogue_parts, DialoguePart, RenderOptions, Renderer};
+use anyhow::Result;
+use std::io::Write;
+
+/// Renders a script as a standalone LaTeX document. The preamble defines a
+/// `sceneheading`, `dialogue`, `action`, `narration` and `transition`
+/// environment whose indents and line lengths are taken from the layout
+/// profile, so the body is plain markup that a typesetter can restyle by
+/// redefining them.
+pub struct LatexRenderer;
+
+impl Default for LatexRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatexRenderer {
+    pub fn new() -> Self {
+        LatexRenderer
+    }
+}
+
+impl Renderer for LatexRenderer {
+    fn name(&self) -> &'static str {
+        "latex"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "tex"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, options: &RenderOptions) -> Result<()> {
+        let profile = &options.profile;
+
+        writeln!(out, "{}", preamble(profile))?;
+        writeln!(out, "\\begin{{document}}")?;
+
+        if let Some(title_page) = &script.title_page {
+            write_title_page(out, title_page)?;
+        }
+        // Page numbers start on the second page of the script itself
+        writeln!(out, "\\thispagestyle{{empty}}")?;
+
+        if !script.characters.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "\\begin{{characters}}")?;
+            for character in &script.characters {
+                writeln!(out, "\\castmember{{{}}}{{{}}}", escape(&character.code), escape(&character.name))?;
+            }
+            writeln!(out, "\\end{{characters}}")?;
+        }
+
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location {
+                writeln!(out)?;
+                writeln!(out, "\\begin{{sceneheading}}")?;
+                writeln!(out, "{}", escape(&location.to_uppercase()))?;
+                writeln!(out, "\\end{{sceneheading}}")?;
+            }
+
+            for element in &scene.elements {
+                writeln!(out)?;
+                match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        writeln!(out, "\\begin{{dialogue}}{{{}}}", escape(&speaker.to_uppercase()))?;
+                        for part in dialogue_parts(text, actions) {
+                            match part {
+                                DialoguePart::Speech(speech) => writeln!(out, "{}", escape_paragraphs(speech))?,
+                                DialoguePart::Parenthetical(wryly) => {
+                                    writeln!(out, "\\parenthetical{{{}}}", escape(wryly))?;
+                                }
+                            }
+                        }
+                        writeln!(out, "\\end{{dialogue}}")?;
+                    }
+                    ScriptElement::Narration { text, .. } => {
+                        writeln!(out, "\\begin{{narration}}")?;
+                        writeln!(out, "{}", escape_paragraphs(text))?;
+                        writeln!(out, "\\end{{narration}}")?;
+                    }
+                    ScriptElement::Action { text, .. } => {
+                        writeln!(out, "\\begin{{action}}")?;
+                        writeln!(out, "({})", escape_paragraphs(text))?;
+                        writeln!(out, "\\end{{action}}")?;
+                    }
+                    ScriptElement::Transition { text, .. } => {
+                        writeln!(out, "\\begin{{transition}}")?;
+                        writeln!(out, "{}", escape(&text.to_uppercase()))?;
+                        writeln!(out, "\\end{{transition}}")?;
+                    }
+                }
+            }
+        }
+
+        writeln!(out)?;
+        writeln!(out, "\\end{{document}}")?;
+        Ok(())
+    }
+}
+
+fn write_title_page(out: &mut dyn Write, title_page: &TitlePage) -> Result<()> {
+    writeln!(out, "\\begin{{titlepage}}")?;
+    writeln!(out, "\\vspace*{{0.3\\textheight}}")?;
+    writeln!(out, "\\begin{{center}}")?;
+    writeln!(out, "{}", escape(&title_page.title.to_uppercase()))?;
+
+    for value in [&title_page.credit, &title_page.author].into_iter().flatten() {
+        writeln!(out, "\\\\[\\baselineskip]")?;
+        writeln!(out, "{}", escape_lines(value))?;
+    }
+    if !title_page.notes.is_empty() {
+        writeln!(out, "\\\\[\\baselineskip]")?;
+        let notes: Vec<String> = title_page.notes.iter().map(|note| escape(note)).collect();
+        writeln!(out, "{}", notes.join(" \\\\\n"))?;
+    }
+    writeln!(out, "\\end{{center}}")?;
+    writeln!(out, "\\vfill")?;
+
+    // Contact details at the bottom left, draft date and copyright at the
+    // bottom right, as on the PDF title page
+    let contact = title_page.contact.as_deref().map(escape_lines).unwrap_or_default();
+    let footer: Vec<String> = [&title_page.draft_date, &title_page.copyright]
+        .into_iter()
+        .flatten()
+        .map(|value| escape_lines(value))
+        .collect();
+    writeln!(out, "\\noindent")?;
+    writeln!(out, "\\begin{{minipage}}[b]{{0.5\\textwidth}}")?;
+    writeln!(out, "{}", contact)?;
+    writeln!(out, "\\end{{minipage}}%")?;
+    writeln!(out, "\\begin{{minipage}}[b]{{0.5\\textwidth}}")?;
+    writeln!(out, "\\raggedleft")?;
+    writeln!(out, "{}", footer.join(" \\\\\n"))?;
+    writeln!(out, "\\end{{minipage}}")?;
+    writeln!(out, "\\end{{titlepage}}")?;
+    Ok(())
+}
+
+/// Document class, page geometry and the screenplay environments for
+/// `profile`. Every block is set ragged right, unhyphenated, in Courier, and
+/// separated from the next by a blank line.
+fn preamble(profile: &LayoutProfile) -> String {
+    let text_width = profile.page_width - profile.left_margin - profile.right_margin;
+    let text_height = profile.lines_per_page as f32 * profile.line_height;
+    let cell = profile.text_width("M");
+    let indent = |x: f32| x - profile.left_margin;
+    // Space left on the right of a block that starts at `x` and is `cells` wide
+    let right = |x: f32, cells: usize| (text_width - indent(x) - cells as f32 * cell).max(0.0);
+    let columns = &profile.columns;
+    let page_numbers = if profile.page_numbers {
+        "\\fancyhead[R]{\\thepage.}"
+    } else {
+        ""
+    };
+
+    format!(
+        r"\documentclass[12pt]{{article}}
+\usepackage[T1]{{fontenc}}
+\usepackage[utf8]{{inputenc}}
+\usepackage{{courier}}
+\usepackage[paperwidth={page_width:.1}mm, paperheight={page_height:.1}mm, left={left:.1}mm, width={text_width:.1}mm, top={top:.1}mm, height={text_height:.1}mm, headheight={line:.1}mm, headsep={headsep:.1}mm]{{geometry}}
+\usepackage{{fancyhdr}}
+
+\renewcommand{{\familydefault}}{{\ttdefault}}
+\pagestyle{{fancy}}
+\fancyhf{{}}
+{page_numbers}
+\renewcommand{{\headrulewidth}}{{0pt}}
+\setlength{{\parindent}}{{0pt}}
+\setlength{{\parskip}}{{0pt}}
+\hyphenpenalty=10000
+\exhyphenpenalty=10000
+\AtBeginDocument{{\fontsize{{{font_size}pt}}{{{line:.2}mm}}\selectfont\raggedright}}
+
+% A block of text between the given left and right indents
+\newenvironment{{screenplayblock}}[2]
+  {{\par\addvspace{{\baselineskip}}\setlength{{\leftskip}}{{#1}}\setlength{{\rightskip}}{{#2 plus 1fil}}\noindent\ignorespaces}}
+  {{\par}}
+
+\newenvironment{{sceneheading}}
+  {{\begin{{screenplayblock}}{{0mm}}{{0mm}}}}
+  {{\end{{screenplayblock}}\nopagebreak}}
+\newenvironment{{narration}}
+  {{\begin{{screenplayblock}}{{0mm}}{{{narration_right:.1}mm}}}}
+  {{\end{{screenplayblock}}}}
+\newenvironment{{action}}
+  {{\begin{{screenplayblock}}{{0mm}}{{{action_right:.1}mm}}}}
+  {{\end{{screenplayblock}}}}
+\newenvironment{{transition}}
+  {{\par\addvspace{{\baselineskip}}\raggedleft\noindent\ignorespaces}}
+  {{\par}}
+
+% Dialogue under a character cue, which never ends up alone at a page break
+\newenvironment{{dialogue}}[1]
+  {{\par\addvspace{{\baselineskip}}{{\setlength{{\leftskip}}{{{character:.1}mm}}\noindent#1\par}}\nopagebreak
+   \setlength{{\leftskip}}{{{dialogue:.1}mm}}\setlength{{\rightskip}}{{{dialogue_right:.1}mm plus 1fil}}\noindent\ignorespaces}}
+  {{\par}}
+\newcommand{{\parenthetical}}[1]
+  {{\par{{\setlength{{\leftskip}}{{{parenthetical:.1}mm}}\setlength{{\rightskip}}{{{parenthetical_right:.1}mm plus 1fil}}\noindent(#1)\par}}\noindent\ignorespaces}}
+
+% The cast list, one code and name per line
+\newenvironment{{characters}}
+  {{\par\noindent CHARACTERS:\par\nopagebreak\setlength{{\leftskip}}{{{dialogue:.1}mm}}}}
+  {{\par\addvspace{{\baselineskip}}}}
+\newcommand{{\castmember}}[2]{{\noindent#1: #2\par}}
+",
+        page_width = profile.page_width,
+        page_height = profile.page_height,
+        left = profile.left_margin,
+        text_width = text_width,
+        top = profile.top_margin,
+        text_height = text_height,
+        line = profile.line_height,
+        headsep = (profile.top_margin - profile.page_number_top - profile.line_height).max(0.0),
+        page_numbers = page_numbers,
+        font_size = profile.font_size,
+        narration_right = right(profile.left_margin, columns.narration),
+        action_right = right(profile.left_margin, columns.action),
+        character = indent(profile.character_indent),
+        dialogue = indent(profile.dialogue_indent),
+        dialogue_right = right(profile.dialogue_indent, columns.dialogue),
+        parenthetical = indent(profile.parenthetical_indent),
+        parenthetical_right = right(profile.parenthetical_indent, columns.parenthetical),
+    )
+}
+
+/// Escapes the characters that LaTeX treats specially in running text.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // Brackets would otherwise be read as the optional argument of a
+            // preceding line break
+            '[' | ']' => {
+                escaped.push('{');
+                escaped.push(c);
+                escaped.push('}');
+            }
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a multi-line metadata value, keeping its line breaks.
+fn escape_lines(text: &str) -> String {
+    text.lines().map(escape).collect::<Vec<_>>().join(" \\\\\n")
+}
+
+/// Escapes `text`, turning the newlines that separate its paragraphs into
+/// paragraph breaks with a blank line between them.
+fn escape_paragraphs(text: &str) -> String {
+    text.split('\n').map(escape).collect::<Vec<_>>().join("\n\n\\vspace{\\baselineskip}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape(r"C:\temp"), r"C:\textbackslash{}temp");
+        assert_eq!(escape("{x}"), r"\{x\}");
+        assert_eq!(escape("$5 & #1 at 50% off_ish"), r"\$5 \& \#1 at 50\% off\_ish");
+        assert_eq!(escape("x^2 ~ y"), r"x\textasciicircum{}2 \textasciitilde{} y");
+        assert_eq!(escape("[beat]"), "{[}beat{]}");
+        assert_eq!(escape("Ça va, naïve?"), "Ça va, naïve?");
+    }
+
+    #[test]
+    fn paragraphs_are_separated_by_a_blank_line() {
+        assert_eq!(escape_paragraphs("One."), "One.");
+        assert_eq!(
+            escape_paragraphs("100% yes.\nNo & no."),
+            "100\\% yes.\n\n\\vspace{\\baselineskip}\nNo \\& no."
+        );
+    }
+
+    #[test]
+    fn lines_are_joined_with_line_breaks() {
+        assert_eq!(escape_lines("Jane Doe"), "Jane Doe");
+        assert_eq!(escape_lines("Jane Doe\njane_doe@example.com"), "Jane Doe \\\\\njane\\_doe@example.com");
+    }
+}
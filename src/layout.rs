@@ -0,0 +1,158 @@
+/// Width of a single Courier glyph as a fraction of the font size. Courier is
+/// monospaced, so every character occupies exactly one cell of this width.
+pub const COURIER_CELL_WIDTH: f32 = 0.6;
+
+const MM_PER_INCH: f32 = 25.4;
+const MM_PER_POINT: f32 = MM_PER_INCH / 72.0;
+
+/// Maximum line lengths, in Courier character cells, for each kind of block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnWidths {
+    pub dialogue: usize,
+    pub parenthetical: usize,
+    pub action: usize,
+    pub narration: usize,
+}
+
+impl Default for ColumnWidths {
+    fn default() -> Self {
+        ColumnWidths {
+            dialogue: 35,
+            parenthetical: 24,
+            action: 60,
+            narration: 60,
+        }
+    }
+}
+
+/// Page geometry used by the PDF renderer. All positions are measured in
+/// millimetres from the left and top edges of the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutProfile {
+    pub name: &'static str,
+    pub page_width: f32,
+    pub page_height: f32,
+    pub font_size: f32,
+    pub line_height: f32,
+    pub top_margin: f32,
+    pub lines_per_page: usize,
+    pub left_margin: f32,
+    pub character_indent: f32,
+    pub dialogue_indent: f32,
+    pub parenthetical_indent: f32,
+    pub right_margin: f32,
+    pub page_number_top: f32,
+    pub page_numbers: bool,
+    pub columns: ColumnWidths,
+}
+
+impl Default for LayoutProfile {
+    fn default() -> Self {
+        Self::spec_screenplay()
+    }
+}
+
+impl LayoutProfile {
+    /// Standard US spec screenplay: US Letter, 12pt Courier, 1.5" left margin,
+    /// character cues at 3.7", dialogue from 2.5" to 6" and 55 lines per page.
+    pub fn spec_screenplay() -> Self {
+        LayoutProfile {
+            name: "spec",
+            page_width: 8.5 * MM_PER_INCH,
+            page_height: 11.0 * MM_PER_INCH,
+            font_size: 12.0,
+            line_height: 12.0 * MM_PER_POINT,
+            top_margin: MM_PER_INCH,
+            lines_per_page: 55,
+            left_margin: 1.5 * MM_PER_INCH,
+            character_indent: 3.7 * MM_PER_INCH,
+            dialogue_indent: 2.5 * MM_PER_INCH,
+            parenthetical_indent: 3.1 * MM_PER_INCH,
+            right_margin: MM_PER_INCH,
+            page_number_top: 0.5 * MM_PER_INCH,
+            page_numbers: true,
+            columns: ColumnWidths::default(),
+        }
+    }
+
+    /// The spec screenplay layout adapted to A4 paper. The extra page height
+    /// fits a few more lines per page; horizontal positions are unchanged.
+    pub fn a4() -> Self {
+        LayoutProfile {
+            name: "a4",
+            page_width: 210.0,
+            page_height: 297.0,
+            lines_per_page: 59,
+            right_margin: 19.5,
+            ..Self::spec_screenplay()
+        }
+    }
+
+    /// Looks up a built-in profile by the name used on the command line.
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "spec" => Some(Self::spec_screenplay()),
+            "a4" => Some(Self::a4()),
+            _ => None,
+        }
+    }
+
+    /// Physical width of `text` in millimetres when set in this profile's font.
+    pub fn text_width(&self, text: &str) -> f32 {
+        measure(text) as f32 * COURIER_CELL_WIDTH * self.font_size * MM_PER_POINT
+    }
+}
+
+/// Number of character cells `text` occupies when set in Courier.
+pub fn measure(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Breaks `text` into lines of at most `width` character cells, splitting on
+/// whitespace. Words longer than a full line are split across lines.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for word in text.split_whitespace() {
+        let mut word = word;
+        let mut word_len = measure(word);
+
+        if current_len > 0 && current_len + 1 + word_len > width {
+            lines.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+
+        // Hard-break words that can never fit on a single line
+        while word_len > width {
+            let split_at = word.char_indices().nth(width).map(|(i, _)| i).unwrap_or(word.len());
+            let (head, tail) = word.split_at(split_at);
+            if current_len > 0 {
+                lines.push(std::mem::take(&mut current));
+                current_len = 0;
+            }
+            lines.push(head.to_string());
+            word = tail;
+            word_len = measure(word);
+        }
+
+        if word.is_empty() {
+            continue;
+        }
+
+        if current_len > 0 {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(word);
+        current_len += word_len;
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
@@ -1,65 +1,163 @@
-use regex::Regex;
+use crate::ast::{Parenthetical, Span, SuppressionScope};
+use crate::grammar::{self, CodeGrammar, Input};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
-    SectionHeader(String),
-    CharacterDef { code: String, name: String },
-    DialogueLine { speaker: String, text: String },
-    NarrationLine(String),
-    ActionText(String),
-    LocationHeader(String),
+    SectionHeader { name: String, span: Span },
+    TitleHeading { title: String, span: Span },
+    TitleField { key: String, value: String, span: Span },
+    TitleText { text: String, span: Span },
+    CharacterDef { code: String, name: String, span: Span },
+    CharacterAttr { key: String, value: String, span: Span },
+    DialogueLine { speaker: String, text: String, parentheticals: Vec<Parenthetical>, span: Span },
+    NarrationLine { text: String, span: Span },
+    ActionText { text: String, span: Span },
+    TransitionLine { text: String, span: Span },
+    LocationHeader { name: String, span: Span },
+    ContinuationLine { text: String, span: Span },
+    Suppression { rules: Vec<String>, scope: SuppressionScope, span: Span },
+    BlankLine { span: Span },
     EOF,
 }
 
+impl Token {
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Token::SectionHeader { span, .. }
+            | Token::TitleHeading { span, .. }
+            | Token::TitleField { span, .. }
+            | Token::TitleText { span, .. }
+            | Token::CharacterDef { span, .. }
+            | Token::CharacterAttr { span, .. }
+            | Token::DialogueLine { span, .. }
+            | Token::NarrationLine { span, .. }
+            | Token::ActionText { span, .. }
+            | Token::TransitionLine { span, .. }
+            | Token::LocationHeader { span, .. }
+            | Token::ContinuationLine { span, .. }
+            | Token::Suppression { span, .. }
+            | Token::BlankLine { span } => Some(span),
+            Token::EOF => None,
+        }
+    }
+}
+
 pub struct Lexer {
     input: String,
+    code_grammar: CodeGrammar,
 }
 
 impl Lexer {
     pub fn new(input: String) -> Self {
         Lexer {
             input,
+            code_grammar: CodeGrammar::default(),
         }
     }
+    
+    pub fn with_code_grammar(mut self, code_grammar: CodeGrammar) -> Self {
+        self.code_grammar = code_grammar;
+        self
+    }
 
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
-        let lines: Vec<&str> = self.input.lines().collect();
         
-        let mut current_section = "";
+        let mut current_section = String::new();
+        let mut current_title_key: Option<String> = None;
+        let mut input = Input::new(self.input.as_str());
         
-        for line in lines {
-            let trimmed = line.trim();
+        while !input.fragment().is_empty() {
+            let Ok((remaining, line)) = grammar::line(input) else {
+                break;
+            };
+            input = remaining;
+            
+            if line.is_blank() {
+                // Blank lines separate blocks in the script, e.g. a dialogue
+                // line from a following stage direction
+                if current_section.eq_ignore_ascii_case("script") {
+                    tokens.push(Token::BlankLine { span: line.span() });
+                }
+                continue;
+            }
+            
+            let content = line.content;
             
-            if trimmed.is_empty() {
+            // Suppression comments may appear in any section
+            if let Ok((_, token)) = grammar::suppression(content) {
+                tokens.push(token);
                 continue;
             }
             
             // Section headers
-            if trimmed.starts_with("## ") {
-                let section_name = trimmed[3..].trim();
-                current_section = section_name;
-                tokens.push(Token::SectionHeader(section_name.to_string()));
+            if let Ok((_, token)) = grammar::section_header(content) {
+                if let Token::SectionHeader { name, .. } = &token {
+                    current_section = name.clone();
+                }
+                tokens.push(token);
                 continue;
             }
             
             // Title section (# header)
-            if trimmed.starts_with("# ") {
-                current_section = "title";
-                tokens.push(Token::SectionHeader("title".to_string()));
+            if let Ok((_, token)) = grammar::title_heading(content) {
+                current_section = "title".to_string();
+                current_title_key = None;
+                tokens.push(token);
                 continue;
             }
             
             match current_section.to_lowercase().as_str() {
+                "title" => {
+                    // Indented lines continue the previous metadata field
+                    let token = match &current_title_key {
+                        Some(key) if line.is_indented() => Token::TitleField {
+                            key: key.clone(),
+                            value: content.fragment().to_string(),
+                            span: line.span(),
+                        },
+                        _ => match grammar::title_line(content) {
+                            Ok((_, token)) => token,
+                            Err(_) => continue,
+                        },
+                    };
+                    current_title_key = match &token {
+                        Token::TitleField { key, .. } => Some(key.clone()),
+                        _ => None,
+                    };
+                    tokens.push(token);
+                }
                 "characters" => {
-                    if let Some(token) = self.parse_character_def(trimmed) {
+                    // Indented lines hold metadata for the character above,
+                    // unless they are a definition of their own
+                    let token = if line.is_indented() {
+                        grammar::character_attr(content).or_else(|_| grammar::character_def(content))
+                    } else {
+                        grammar::character_def(content)
+                    };
+                    if let Ok((_, token)) = token {
                         tokens.push(token);
                     }
                 }
                 "script" => {
-                    if let Some(token) = self.parse_script_line(trimmed) {
-                        tokens.push(token);
-                    }
+                    // Indented lines continue the previous speech or narration,
+                    // unless they are a script line of their own
+                    let Ok((_, token)) = grammar::script_line(self.code_grammar)(content) else {
+                        continue;
+                    };
+                    let token = match token {
+                        // Narration keeps an escaped line's text without the `\`
+                        Token::NarrationLine { text, .. } if line.is_indented() => Token::ContinuationLine {
+                            text,
+                            span: line.span(),
+                        },
+                        Token::ActionText { .. } if line.is_indented() => Token::ContinuationLine {
+                            text: content.fragment().to_string(),
+                            span: line.span(),
+                        },
+                        token => token,
+                    };
+                    tokens.push(token);
                 }
                 _ => {}
             }
@@ -68,39 +166,4 @@ impl Lexer {
         tokens.push(Token::EOF);
         tokens
     }
-    
-    fn parse_character_def(&self, line: &str) -> Option<Token> {
-        let re = Regex::new(r"^([A-Z]+):\s*(.+)$").unwrap();
-        if let Some(captures) = re.captures(line) {
-            let code = captures.get(1)?.as_str().to_string();
-            let name = captures.get(2)?.as_str().to_string();
-            return Some(Token::CharacterDef { code, name });
-        }
-        None
-    }
-    
-    fn parse_script_line(&self, line: &str) -> Option<Token> {
-        // Location header [Location Name]
-        if line.starts_with('[') && line.ends_with(']') {
-            let location = line[1..line.len()-1].to_string();
-            return Some(Token::LocationHeader(location));
-        }
-        
-        // Action text (action description)
-        if line.starts_with('(') && line.ends_with(')') {
-            let action = line[1..line.len()-1].to_string();
-            return Some(Token::ActionText(action));
-        }
-        
-        // Dialogue line ABC: dialogue text
-        let re = Regex::new(r"^([A-Z]+):\s*(.+)$").unwrap();
-        if let Some(captures) = re.captures(line) {
-            let speaker = captures.get(1)?.as_str().to_string();
-            let text = captures.get(2)?.as_str().to_string();
-            return Some(Token::DialogueLine { speaker, text });
-        }
-        
-        // Narration line (default)
-        Some(Token::NarrationLine(line.to_string()))
-    }
-}
\ No newline at end of file
+}
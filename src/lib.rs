@@ -1,11 +1,39 @@
 pub mod ast;
+pub mod diagnostic;
+pub mod emitter;
+pub mod fdx;
+pub mod fix;
+pub mod fountain;
+pub mod grammar;
+pub mod html;
+pub mod interchange;
+pub mod latex;
+pub mod layout;
 pub mod lexer;
+pub mod markdown;
 pub mod parser;
+pub mod pdf;
 pub mod renderer;
+pub mod report;
+pub mod rules;
 pub mod validator;
 
 pub use ast::*;
+pub use diagnostic::*;
+pub use emitter::*;
+pub use fdx::*;
+pub use fix::*;
+pub use fountain::*;
+pub use grammar::*;
+pub use html::*;
+pub use interchange::*;
+pub use latex::*;
+pub use layout::*;
 pub use lexer::*;
+pub use markdown::*;
 pub use parser::*;
+pub use pdf::*;
 pub use renderer::*;
+pub use report::*;
+pub use rules::*;
 pub use validator::*;
\ No newline at end of file
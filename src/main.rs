@@ -1,84 +1,366 @@
 pub mod ast;
+pub mod diagnostic;
+pub mod emitter;
+pub mod fdx;
+pub mod fix;
+pub mod fountain;
+pub mod grammar;
+pub mod html;
+pub mod interchange;
+pub mod latex;
+pub mod layout;
 pub mod lexer;
+pub mod markdown;
 pub mod parser;
+pub mod pdf;
 pub mod renderer;
+pub mod report;
+pub mod rules;
 pub mod validator;
 
-use clap::{Arg, Command};
-use std::fs;
-use anyhow::Result;
+use clap::{Arg, ArgMatches, Command};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use anyhow::{anyhow, Result};
 
+use ast::Script;
+use emitter::Emitter;
+use fdx::FdxParser;
+use fountain::FountainParser;
+use layout::LayoutProfile;
+use grammar::CodeGrammar;
+use interchange::AstFormat;
 use lexer::Lexer;
 use parser::Parser;
-use renderer::PdfRenderer;
+use renderer::{RenderOptions, RendererRegistry};
+use report::ReportFormat;
+use rules::{RuleRegistry, ValidatorConfig};
 use validator::Validator;
 
 use log::{info, debug};
 
 fn main() -> Result<()> {
+    let renderers = RendererRegistry::builtin();
+    let backend_names: Vec<&'static str> = renderers.names().collect();
+
+    let input_arg = Arg::new("input")
+        .help("Input markdown file, a Fountain (.fountain) or Final Draft (.fdx) file, or a JSON or YAML AST")
+        .required(true)
+        .index(1);
+    let narrator_arg = Arg::new("narrator")
+        .long("narrator")
+        .value_name("CODE")
+        .help("Treat lines from this character code as narration (in addition to N)")
+        .action(clap::ArgAction::Append);
+    let config_arg = Arg::new("config")
+        .short('c')
+        .long("config")
+        .value_name("FILE")
+        .help("Validator configuration (default: nearest script-parser.toml)");
+
     let matches = Command::new("script-parser")
         .version(env!("CARGO_PKG_VERSION"))
         .about("Parses screenplay markdown files and generates PDF output")
-        .arg(
-            Arg::new("input")
-                .help("Input markdown file")
-                .required(true)
-                .index(1),
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("convert")
+                .about("Writes the parsed script as a JSON or YAML document")
+                .arg(input_arg.clone())
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("FORMAT")
+                        .help("Document format")
+                        .value_parser(["json", "yaml"])
+                        .default_value("json"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Output file (default: standard output)"),
+                )
+                .arg(narrator_arg.clone())
+                .arg(config_arg.clone()),
         )
+        .arg(input_arg)
         .arg(
             Arg::new("output")
                 .short('o')
                 .long("output")
                 .value_name("FILE")
-                .help("Output PDF file")
-                .default_value("output.pdf"),
+                .help("Output file (default: output.<format extension>)"),
         )
         .arg(
             Arg::new("validate-only")
                 .short('v')
                 .long("validate-only")
-                .help("Only validate, don't generate PDF")
+                .help("Only validate, don't generate output")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("layout")
+                .short('l')
+                .long("layout")
+                .value_name("PROFILE")
+                .help("Page layout profile")
+                .value_parser(["spec", "a4"])
+                .default_value("spec"),
+        )
+        .arg(narrator_arg)
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format")
+                .value_parser(backend_names)
+                .default_value("pdf"),
+        )
+        .arg(
+            Arg::new("report")
+                .short('r')
+                .long("report")
+                .value_name("FORMAT")
+                .help("Validation report format, written to standard output unless human")
+                .value_parser(["human", "json", "sarif"])
+                .default_value("human"),
+        )
+        .arg(config_arg)
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .help("Apply suggested fixes to the input file and show the changes")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("With --fix, show the changes without writing them")
+                .action(clap::ArgAction::SetTrue)
+                .requires("fix"),
+        )
         .get_matches();
 
+    if let Some(("convert", matches)) = matches.subcommand() {
+        return convert(matches);
+    }
+
     let input_file = matches.get_one::<String>("input").unwrap();
-    let output_file = matches.get_one::<String>("output").unwrap();
     let validate_only = matches.get_flag("validate-only");
+    let layout_name = matches.get_one::<String>("layout").unwrap();
+    let format = matches.get_one::<String>("format").unwrap();
+    let report_format = matches.get_one::<String>("report").unwrap().parse::<ReportFormat>()?;
+    let fix = matches.get_flag("fix");
+    let dry_run = matches.get_flag("dry-run");
+    let narrator_codes = narrator_codes(&matches);
+
+    let renderer = if validate_only {
+        None
+    } else {
+        Some(renderers.get(format).ok_or_else(|| anyhow!("Unknown output format '{}'", format))?)
+    };
+    if fix && report_format != ReportFormat::Human {
+        return Err(anyhow!("--fix cannot be combined with --report {}", matches.get_one::<String>("report").unwrap()));
+    }
+    let input_format = InputFormat::from_path(Path::new(input_file));
+    if fix && input_format != InputFormat::Markdown {
+        return Err(anyhow!("--fix only supports markdown scripts"));
+    }
+
+    let config = load_config(&matches, input_file)?;
+    let code_grammar = config.character_codes;
+    let validator = Validator::from_config(config).with_narrator_codes(narrator_codes.iter().cloned());
 
     info!("Reading input file: {}", input_file);
-    let content = fs::read_to_string(input_file)?;
+    let mut content = fs::read_to_string(input_file)?;
+    let mut script = parse_source(&content, input_format, code_grammar, &narrator_codes)?;
+
+    info!("Validating script...");
+    let mut report = validator.validate(&script);
 
-    info!("Tokenizing...");
-    let mut lexer = Lexer::new(content);
-    let tokens = lexer.tokenize();
-    debug!("Generated {} tokens", tokens.len());
+    if fix {
+        let outcome = fix::apply_fixes(&content, report.diagnostics());
+        if outcome.is_changed() {
+            print!("{}", fix::diff(&content, &outcome.source, input_file));
+        }
+        if outcome.skipped > 0 {
+            eprintln!("{} overlapping fix(es) were not applied; run --fix again", outcome.skipped);
+        }
 
-    info!("Parsing...");
-    let mut parser = Parser::new(tokens);
-    let script = parser.parse()?;
+        if dry_run {
+            eprintln!("Would fix {} issue(s) in {}", outcome.applied, input_file);
+        } else if outcome.is_changed() {
+            fs::write(input_file, &outcome.source)?;
+            eprintln!("Fixed {} issue(s) in {}", outcome.applied, input_file);
 
-    debug!("Script parsed successfully! Title section: {}, Characters: {}, Scenes: {}",
-        if script.title_section.is_empty() { "empty" } else { "present" },
-        script.characters.len(),
-        script.scenes.len()
-    );
+            // Report only what is left after fixing
+            content = outcome.source;
+            script = parse_source(&content, input_format, code_grammar, &narrator_codes)?;
+            report = validator.validate(&script);
+        }
+    }
 
-    info!("Validating script...");
-    let mut validator = Validator::new();
-    validator.validate(&script)?;
+    match report_format {
+        ReportFormat::Human => {
+            let color = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+            // The spans of a serialized AST point into the script it was
+            // converted from, so there is no source to quote
+            let source = if matches!(input_format, InputFormat::Ast(_)) { "" } else { content.as_str() };
+            let emitter = Emitter::new(source, input_file).with_color(color);
+            for diagnostic in report.diagnostics() {
+                eprintln!("{}", emitter.render(diagnostic));
+            }
+        }
+        ReportFormat::Json => println!("{}", report::to_json(&report, input_file)?),
+        ReportFormat::Sarif => println!("{}", report::to_sarif(&report, input_file)?),
+    }
+
+    if report.has_errors() {
+        return Err(anyhow!("Validation failed with {} error(s)", report.errors().count()));
+    }
 
     debug!("Script validation completed successfully!");
 
-    if validate_only {
-        info!("Validation complete. No PDF generated.");
+    let Some(renderer) = renderer else {
+        info!("Validation complete. No output generated.");
         return Ok(());
-    }
+    };
 
-    info!("Generating PDF: {}", output_file);
-    let renderer = PdfRenderer::new();
-    renderer.render(&script, output_file)?;
+    let output_file = match matches.get_one::<String>("output") {
+        Some(path) => path.clone(),
+        None => format!("output.{}", renderer.file_extension()),
+    };
+    info!("Generating {}: {}", renderer.name(), output_file);
+    let profile = LayoutProfile::by_name(layout_name)
+        .ok_or_else(|| anyhow!("Unknown layout profile '{}'", layout_name))?;
+    let options = RenderOptions::new().with_profile(profile);
+    let mut out = BufWriter::new(File::create(&output_file)?);
+    renderer.render(&script, &mut out, &options)?;
+    out.flush()?;
 
-    info!("PDF generated successfully!");
+    info!("Output generated successfully!");
     Ok(())
 }
+
+/// `script-parser convert`: parses the input and writes its AST as JSON or
+/// YAML, without validating it.
+fn convert(matches: &ArgMatches) -> Result<()> {
+    let input_file = matches.get_one::<String>("input").unwrap();
+    let format = matches.get_one::<String>("to").unwrap().parse::<AstFormat>()?;
+    let config = load_config(matches, input_file)?;
+
+    info!("Reading input file: {}", input_file);
+    let content = fs::read_to_string(input_file)?;
+    let input_format = InputFormat::from_path(Path::new(input_file));
+    let script = parse_source(&content, input_format, config.character_codes, &narrator_codes(matches))?;
+
+    let document = format.serialize(&script)?;
+    match matches.get_one::<String>("output") {
+        Some(output_file) => {
+            info!("Writing AST: {}", output_file);
+            fs::write(output_file, document)?;
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
+
+fn narrator_codes(matches: &ArgMatches) -> Vec<String> {
+    matches
+        .get_many::<String>("narrator")
+        .unwrap_or_default()
+        .cloned()
+        .collect()
+}
+
+/// Loads the `--config` file, or else the nearest `script-parser.toml` above
+/// the input file, falling back to the defaults.
+fn load_config(matches: &ArgMatches, input_file: &str) -> Result<ValidatorConfig> {
+    let config_file = match matches.get_one::<String>("config") {
+        Some(path) => Some(Path::new(path).to_path_buf()),
+        None => fs::canonicalize(input_file)?
+            .parent()
+            .and_then(ValidatorConfig::discover),
+    };
+    let config = match &config_file {
+        Some(path) => {
+            info!("Using validator configuration: {}", path.display());
+            ValidatorConfig::load(path)?
+        }
+        None => ValidatorConfig::default(),
+    };
+    config.check_rules(&RuleRegistry::builtin())?;
+    Ok(config)
+}
+
+/// The language an input file is written in, told apart by its extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    Markdown,
+    Fountain,
+    FinalDraft,
+    /// A script already parsed and serialized by `script-parser convert`
+    Ast(AstFormat),
+}
+
+impl InputFormat {
+    fn from_path(path: &Path) -> Self {
+        if let Some(format) = AstFormat::from_path(path) {
+            return InputFormat::Ast(format);
+        }
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("fountain") => InputFormat::Fountain,
+            Some(extension) if extension.eq_ignore_ascii_case("fdx") => InputFormat::FinalDraft,
+            _ => InputFormat::Markdown,
+        }
+    }
+}
+
+fn parse_source(
+    content: &str,
+    input_format: InputFormat,
+    code_grammar: CodeGrammar,
+    narrator_codes: &[String],
+) -> Result<Script> {
+    let script = match input_format {
+        InputFormat::Markdown => {
+            info!("Tokenizing...");
+            let mut lexer = Lexer::new(content.to_string()).with_code_grammar(code_grammar);
+            let tokens = lexer.tokenize();
+            debug!("Generated {} tokens", tokens.len());
+
+            info!("Parsing...");
+            let mut parser = Parser::new(tokens).with_narrator_codes(narrator_codes.iter().cloned());
+            parser.parse()?
+        }
+        InputFormat::Fountain => {
+            info!("Parsing Fountain...");
+            FountainParser::new(content.to_string())
+                .with_narrator_codes(narrator_codes.iter().cloned())
+                .parse()?
+        }
+        InputFormat::FinalDraft => {
+            info!("Parsing Final Draft...");
+            FdxParser::new(content.to_string())
+                .with_narrator_codes(narrator_codes.iter().cloned())
+                .parse()?
+        }
+        InputFormat::Ast(format) => {
+            info!("Reading {:?} AST...", format);
+            format.deserialize(content)?
+        }
+    };
+
+    debug!("Script parsed successfully! Title: {}, Characters: {}, Scenes: {}",
+        script.title_page.as_ref().map_or("missing", |page| page.title.as_str()),
+        script.characters.len(),
+        script.scenes.len()
+    );
+
+    Ok(script)
+}
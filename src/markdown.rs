@@ -0,0 +1,143 @@
+use crate::ast::{Script, ScriptElement, TitlePage};
+use crate::grammar::{self, CodeGrammar, Input};
+use crate::lexer::Token;
+use crate::parser::restore_parentheticals;
+use crate::renderer::{RenderOptions, Renderer};
+use anyhow::Result;
+use std::io::Write;
+
+/// Writes a script back out in this project's Markdown DSL, e.g. to convert
+/// a Fountain file. Parsing the output gives back the same script: narration
+/// that would read as another kind of line is escaped with a leading `\`.
+pub struct MarkdownRenderer;
+
+impl Default for MarkdownRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownRenderer {
+    pub fn new() -> Self {
+        MarkdownRenderer
+    }
+}
+
+impl Renderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, _options: &RenderOptions) -> Result<()> {
+        // Everything is written as blocks separated by a blank line
+        let mut blocks = Vec::new();
+
+        if let Some(title_page) = &script.title_page {
+            blocks.push(format!("# {}", title_page.title));
+            let fields = title_page_fields(title_page);
+            if !fields.is_empty() {
+                blocks.push(fields);
+            }
+        }
+
+        if !script.characters.is_empty() {
+            blocks.push("## Characters".to_string());
+            let mut lines = Vec::new();
+            for character in &script.characters {
+                lines.push(format!("{}: {}", character.code, character.name));
+                if let Some(description) = &character.description {
+                    lines.push(format!("  description: {}", description));
+                }
+                if let Some(age) = &character.age {
+                    lines.push(format!("  age: {}", age));
+                }
+                if !character.aliases.is_empty() {
+                    lines.push(format!("  aliases: {}", character.aliases.join(", ")));
+                }
+            }
+            blocks.push(lines.join("\n"));
+        }
+
+        if !script.scenes.is_empty() {
+            blocks.push("## Script".to_string());
+        }
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location {
+                blocks.push(format!("[{}]", location));
+            }
+
+            for element in &scene.elements {
+                blocks.push(match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        paragraphs(&format!("{}: ", speaker), &restore_parentheticals(text, actions))
+                    }
+                    ScriptElement::Narration { text, voice: Some(voice), .. } => {
+                        paragraphs(&format!("{}: ", voice), text)
+                    }
+                    ScriptElement::Narration { text, voice: None, .. } => paragraphs("", text),
+                    ScriptElement::Action { text, .. } => format!("({})", text),
+                    ScriptElement::Transition { text, .. } => format!("> {}", text),
+                });
+            }
+        }
+
+        if !blocks.is_empty() {
+            writeln!(out, "{}", blocks.join("\n\n"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The `Key: value` lines of the title section, with the lines of multi-line
+/// values indented below the first, followed by the notes.
+fn title_page_fields(title_page: &TitlePage) -> String {
+    let mut lines = Vec::new();
+    for (key, value) in [
+        ("Credit", &title_page.credit),
+        ("Author", &title_page.author),
+        ("Draft date", &title_page.draft_date),
+        ("Contact", &title_page.contact),
+        ("Copyright", &title_page.copyright),
+    ] {
+        if let Some(value) = value {
+            for (index, line) in value.lines().enumerate() {
+                if index == 0 {
+                    lines.push(format!("{}: {}", key, line));
+                } else {
+                    lines.push(format!("  {}", line));
+                }
+            }
+        }
+    }
+    lines.extend(title_page.notes.iter().cloned());
+    lines.join("\n")
+}
+
+/// `text` after `prefix`, with each further paragraph on an indented line
+/// after a blank line, so it continues the same element.
+fn paragraphs(prefix: &str, text: &str) -> String {
+    let mut paragraphs = text.split('\n').map(str::trim).filter(|paragraph| !paragraph.is_empty());
+    let first = paragraphs.next().unwrap_or_default();
+    let mut block = if prefix.is_empty() { escape(first) } else { format!("{}{}", prefix, first) };
+    for paragraph in paragraphs {
+        block.push_str("\n\n  ");
+        block.push_str(&escape(paragraph));
+    }
+    block
+}
+
+/// `line` with a leading `\` if the lexer would not read it as narration,
+/// e.g. `WARNING: Contents hot.` or `[sic]`.
+fn escape(line: &str) -> String {
+    let input = Input::new(line);
+    let plain = !line.starts_with('\\')
+        && grammar::suppression(input).is_err()
+        && grammar::section_header(input).is_err()
+        && grammar::title_heading(input).is_err()
+        && matches!(grammar::script_line(CodeGrammar::lenient())(input), Ok((_, Token::NarrationLine { .. })));
+    if plain { line.to_string() } else { format!("\\{}", line) }
+}
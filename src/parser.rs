@@ -1,29 +1,60 @@
-use crate::ast::{Script, Scene, ScriptElement};
+use crate::ast::{Character, CharacterTable, Parenthetical, Script, Scene, ScriptElement, Span, Suppression, TitlePage, NARRATOR_CODE};
+use crate::grammar::split_parentheticals;
 use crate::lexer::Token;
 use anyhow::Result;
-use std::collections::HashMap;
 
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
+    narrator_codes: NarratorCodes,
+    suppressions: Vec<Suppression>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, position: 0 }
+        // Suppression comments can sit anywhere, so they are set aside rather
+        // than interrupting the blocks around them
+        let (comments, tokens): (Vec<Token>, Vec<Token>) = tokens
+            .into_iter()
+            .partition(|token| matches!(token, Token::Suppression { .. }));
+        let suppressions = comments
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Suppression { rules, scope, span } => Some(Suppression::new(rules, scope, span)),
+                _ => None,
+            })
+            .collect();
+        
+        Parser {
+            tokens,
+            position: 0,
+            narrator_codes: NarratorCodes::default(),
+            suppressions,
+        }
+    }
+    
+    /// Adds character codes whose lines are parsed as narration, in addition
+    /// to the reserved `N` code.
+    pub fn with_narrator_codes<I, S>(mut self, codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.narrator_codes.extend(codes);
+        self
     }
     
     pub fn parse(&mut self) -> Result<Script> {
         let mut script = Script::new();
+        script.suppressions = self.suppressions.clone();
         
         while !self.is_at_end() {
             match self.current_token() {
-                Token::SectionHeader(section) => {
-                    match section.to_lowercase().as_str() {
-                        "title" => {
-                            script.title_section = "Title Section".to_string();
-                            self.advance();
-                        }
+                Token::TitleHeading { .. } => {
+                    script.title_page = Some(self.parse_title_page());
+                }
+                Token::SectionHeader { name, .. } => {
+                    match name.to_lowercase().as_str() {
                         "characters" => {
                             script.characters = self.parse_characters()?;
                         }
@@ -44,68 +75,169 @@ impl Parser {
         Ok(script)
     }
     
-    fn parse_characters(&mut self) -> Result<HashMap<String, String>> {
-        let mut characters = HashMap::new();
+    fn parse_title_page(&mut self) -> TitlePage {
+        let mut title_page = match self.current_token() {
+            Token::TitleHeading { title, span } => TitlePage::new(title.clone(), span.clone()),
+            _ => TitlePage::default(),
+        };
+        self.advance(); // Skip the "# Title" heading
+        
+        while !self.is_at_end() {
+            match self.current_token() {
+                Token::TitleField { key, value, span } => {
+                    title_page.set_field(key, value);
+                    title_page.span = title_page.span.to(span);
+                    self.advance();
+                }
+                Token::TitleText { text, span } => {
+                    title_page.notes.push(text.clone());
+                    title_page.span = title_page.span.to(span);
+                    self.advance();
+                }
+                Token::SectionHeader { .. } | Token::TitleHeading { .. } => break,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+        
+        title_page
+    }
+    
+    fn parse_characters(&mut self) -> Result<CharacterTable> {
+        let mut characters = CharacterTable::new();
+        let mut current: Option<Character> = None;
         self.advance(); // Skip the "Characters" header
         
         while !self.is_at_end() {
             match self.current_token() {
-                Token::CharacterDef { code, name } => {
-                    characters.insert(code.clone(), name.clone());
+                Token::CharacterDef { code, name, span } => {
+                    if let Some(character) = current.take() {
+                        characters.insert(character);
+                    }
+                    current = Some(Character::new(code.clone(), name.clone(), span.clone()));
+                    self.advance();
+                }
+                Token::CharacterAttr { key, value, span } => {
+                    if let Some(character) = current.as_mut() {
+                        character.set_attribute(key, value);
+                        character.span = character.span.to(span);
+                    }
                     self.advance();
                 }
-                Token::SectionHeader(_) => break,
+                Token::SectionHeader { .. } => break,
                 _ => {
                     self.advance();
                 }
             }
         }
         
+        if let Some(character) = current {
+            characters.insert(character);
+        }
+        
         Ok(characters)
     }
     
     fn parse_script(&mut self) -> Result<Vec<Scene>> {
         let mut scenes = Vec::new();
-        let mut current_scene = Scene::new(None);
+        let mut current_scene = Scene::new(None, Span::default());
+        // The block the previous line belonged to, if the current line can continue it
+        let mut continuing = Block::None;
+        // Whether blank lines separate the current line from the previous element
+        let mut paragraph_break = false;
         
         self.advance(); // Skip the "Script" header
         
         while !self.is_at_end() {
+            let previous = std::mem::replace(&mut continuing, Block::None);
+            let separated = std::mem::take(&mut paragraph_break);
             match self.current_token() {
-                Token::LocationHeader(location) => {
-                    if !current_scene.elements.is_empty() {
-                        scenes.push(current_scene);
+                Token::LocationHeader { name, span } => {
+                    let next_scene = Scene::new(Some(name.clone()), span.clone());
+                    let previous_scene = std::mem::replace(&mut current_scene, next_scene);
+                    // Scenes with a heading are kept even when empty, so they can be reported
+                    if !previous_scene.elements.is_empty() || previous_scene.location.is_some() {
+                        scenes.push(previous_scene);
                     }
-                    current_scene = Scene::new(Some(location.clone()));
                     self.advance();
                 }
-                Token::DialogueLine { speaker, text } => {
-                    let element = ScriptElement::Dialogue {
-                        speaker: speaker.clone(),
+                Token::DialogueLine { speaker, text, parentheticals, span } => {
+                    let element =
+                        self.narrator_codes.speech(speaker.clone(), text.clone(), parentheticals.clone(), span.clone());
+                    continuing = match element {
+                        ScriptElement::Narration { .. } => Block::Narration,
+                        _ => Block::Dialogue,
+                    };
+                    current_scene.push(element);
+                    self.advance();
+                }
+                Token::ActionText { text, span } if previous == Block::Dialogue => {
+                    // A parenthetical directly below a dialogue line is a wryly
+                    let line = format!("({})", text);
+                    continue_last(&mut current_scene, &line, span, "");
+                    continuing = Block::Dialogue;
+                    self.advance();
+                }
+                Token::NarrationLine { text, span } if previous != Block::None => {
+                    // Lines without a blank line in between belong to the same block
+                    continue_last(&mut current_scene, text, span, " ");
+                    continuing = previous;
+                    self.advance();
+                }
+                Token::ContinuationLine { text, span } => {
+                    let separator = if separated { "\n" } else { " " };
+                    continuing = match continue_last(&mut current_scene, text, span, separator) {
+                        Some(block) => block,
+                        None => {
+                            current_scene.push(ScriptElement::Narration {
+                                text: text.clone(),
+                                voice: None,
+                                span: span.clone(),
+                            });
+                            Block::Narration
+                        }
+                    };
+                    self.advance();
+                }
+                Token::NarrationLine { text, span } => {
+                    let element = ScriptElement::Narration {
                         text: text.clone(),
-                        actions: Vec::new(),
+                        voice: None,
+                        span: span.clone(),
                     };
-                    current_scene.elements.push(element);
+                    current_scene.push(element);
+                    continuing = Block::Narration;
                     self.advance();
                 }
-                Token::NarrationLine(text) => {
-                    let element = ScriptElement::Narration(text.clone());
-                    current_scene.elements.push(element);
+                Token::ActionText { text, span } => {
+                    let element = ScriptElement::Action {
+                        text: text.clone(),
+                        span: span.clone(),
+                    };
+                    current_scene.push(element);
+                    self.advance();
+                }
+                Token::TransitionLine { text, span } => {
+                    let element = ScriptElement::Transition {
+                        text: text.clone(),
+                        span: span.clone(),
+                    };
+                    current_scene.push(element);
                     self.advance();
                 }
-                Token::ActionText(text) => {
-                    let element = ScriptElement::Action(text.clone());
-                    current_scene.elements.push(element);
+                Token::BlankLine { .. } => {
+                    paragraph_break = !current_scene.elements.is_empty();
                     self.advance();
                 }
-                Token::SectionHeader(_) => break,
+                Token::SectionHeader { .. } => break,
                 _ => {
                     self.advance();
                 }
             }
         }
         
-        if !current_scene.elements.is_empty() {
+        if !current_scene.elements.is_empty() || current_scene.location.is_some() {
             scenes.push(current_scene);
         }
         
@@ -125,4 +257,155 @@ impl Parser {
     fn is_at_end(&self) -> bool {
         self.position >= self.tokens.len() || matches!(self.current_token(), Token::EOF)
     }
-}
\ No newline at end of file
+}
+
+/// The character codes whose lines are narration rather than dialogue: the
+/// reserved `N` code, and any others passed with `--narrator`. Shared by the
+/// Markdown, Fountain and Final Draft parsers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NarratorCodes {
+    codes: Vec<String>,
+}
+
+impl Default for NarratorCodes {
+    fn default() -> Self {
+        NarratorCodes {
+            codes: vec![NARRATOR_CODE.to_string()],
+        }
+    }
+}
+
+impl NarratorCodes {
+    pub fn extend<I, S>(&mut self, codes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.codes.extend(codes.into_iter().map(Into::into));
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.codes.iter().any(|narrator| narrator == code)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.codes.iter()
+    }
+
+    /// The element for a speech by `speaker`: narration with its
+    /// parentheticals put back into the text if `speaker` is a narrator code,
+    /// dialogue otherwise. Parenthetical offsets past the end of the text are
+    /// moved to its end.
+    pub fn speech(&self, speaker: String, text: String, mut actions: Vec<Parenthetical>, span: Span) -> ScriptElement {
+        for action in &mut actions {
+            action.offset = action.offset.min(text.len());
+        }
+        if self.contains(&speaker) {
+            ScriptElement::Narration {
+                text: restore_parentheticals(&text, &actions),
+                voice: Some(speaker),
+                span,
+            }
+        } else {
+            ScriptElement::Dialogue { speaker, text, actions, span }
+        }
+    }
+}
+
+/// Kinds of block that a following line can continue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    None,
+    Dialogue,
+    Narration,
+}
+
+/// Puts parentheticals that the lexer split out of a line back into its text,
+/// for lines that turn out not to be dialogue.
+pub(crate) fn restore_parentheticals(text: &str, parentheticals: &[Parenthetical]) -> String {
+    let mut pieces = Vec::new();
+    let mut position = 0;
+    for parenthetical in parentheticals {
+        let offset = parenthetical.position_in(text, position);
+        let before = text[position..offset].trim_matches(' ');
+        if !before.is_empty() {
+            pieces.push(before.to_string());
+        }
+        pieces.push(format!("({})", parenthetical.text));
+        position = offset;
+    }
+    let rest = text[position..].trim_matches(' ');
+    if !rest.is_empty() {
+        pieces.push(rest.to_string());
+    }
+    pieces.join(" ")
+}
+
+/// Appends a continuation line to the scene's last dialogue or narration
+/// element, joined with `separator` ("\n" marks a paragraph break).
+/// Returns `None` if there is no element that can be continued.
+fn continue_last(scene: &mut Scene, line: &str, span: &Span, separator: &str) -> Option<Block> {
+    let block = match scene.elements.last_mut()? {
+        ScriptElement::Dialogue { text, actions, span: element_span, .. } => {
+            let (addition, parentheticals) = split_parentheticals(line, 0, span);
+            let base = if text.is_empty() || addition.is_empty() {
+                text.len()
+            } else {
+                text.len() + separator.len()
+            };
+            actions.extend(parentheticals.into_iter().map(|parenthetical| Parenthetical {
+                offset: base + parenthetical.offset,
+                ..parenthetical
+            }));
+            if !addition.is_empty() {
+                if !text.is_empty() {
+                    text.push_str(separator);
+                }
+                text.push_str(&addition);
+            }
+            *element_span = element_span.to(span);
+            Block::Dialogue
+        }
+        ScriptElement::Narration { text, span: element_span, .. } => {
+            text.push_str(separator);
+            text.push_str(line);
+            *element_span = element_span.to(span);
+            Block::Narration
+        }
+        ScriptElement::Action { .. } | ScriptElement::Transition { .. } => return None,
+    };
+    scene.span = scene.span.to(span);
+    Some(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parenthetical(text: &str, offset: usize) -> Parenthetical {
+        Parenthetical {
+            text: text.to_string(),
+            offset,
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn parentheticals_are_restored_where_they_occurred() {
+        let actions = [parenthetical("quietly", 0), parenthetical("beat", 7), parenthetical("sighs", 12)];
+        assert_eq!(restore_parentheticals("Ça va bien", &actions), "(quietly) Ça va (beat) bien (sighs)");
+    }
+
+    #[test]
+    fn offsets_inside_a_character_are_moved_back() {
+        // Byte 1 falls inside the two-byte `Ç`
+        assert_eq!(restore_parentheticals("Ça va", &[parenthetical("beat", 1)]), "(beat) Ça va");
+        assert_eq!(restore_parentheticals("né", &[parenthetical("beat", 2)]), "n (beat) é");
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_clamped() {
+        let actions = [parenthetical("beat", 4), parenthetical("sighs", 2), parenthetical("laughs", 99)];
+        assert_eq!(restore_parentheticals("Non, non", &actions), "Non, (beat) (sighs) non (laughs)");
+    }
+}
@@ -0,0 +1,332 @@
+use crate::ast::{Parenthetical, Script, ScriptElement, TitlePage};
+use crate::layout::{self, LayoutProfile};
+use crate::renderer::{dialogue_parts, DialoguePart, RenderOptions, Renderer};
+use printpdf::*;
+use anyhow::Result;
+use std::io::{BufWriter, Write};
+
+/// Renders a script as a paginated PDF laid out by the options' profile.
+pub struct PdfRenderer;
+
+impl Default for PdfRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfRenderer {
+    pub fn new() -> Self {
+        PdfRenderer
+    }
+}
+
+impl Renderer for PdfRenderer {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn render(&self, script: &Script, out: &mut dyn Write, options: &RenderOptions) -> Result<()> {
+        let profile = &options.profile;
+        let (doc, page1, layer1) = PdfDocument::new(
+            "Script",
+            Mm(profile.page_width),
+            Mm(profile.page_height),
+            "Layer 1",
+        );
+        let font = doc.add_builtin_font(BuiltinFont::Courier)?;
+
+        let mut cursor = PageCursor::new(&doc, profile, &font, doc.get_page(page1).get_layer(layer1));
+
+        // Title page, followed by the script starting on its own page 1
+        if let Some(title_page) = &script.title_page {
+            self.draw_title_page(&mut cursor, title_page);
+            cursor.restart_numbering();
+        }
+
+        // Characters section
+        if !script.characters.is_empty() {
+            // Keep the heading together with at least the first character
+            cursor.ensure_lines(2);
+            cursor.text("CHARACTERS:", profile.left_margin);
+            cursor.advance(1);
+
+            for character in &script.characters {
+                let char_line = format!("{}: {}", character.code, character.name);
+                cursor.ensure_lines(1);
+                cursor.text(&char_line, profile.dialogue_indent);
+                cursor.advance(1);
+            }
+            cursor.blank_line();
+        }
+
+        // Script content
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location {
+                // Never leave a scene heading orphaned at the bottom of a page
+                cursor.ensure_lines(3);
+                cursor.text(&location.to_uppercase(), profile.left_margin);
+                cursor.advance(1);
+                cursor.blank_line();
+            }
+
+            for element in &scene.elements {
+                match element {
+                    ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                        self.draw_speech(&mut cursor, speaker, text, actions);
+                    }
+                    ScriptElement::Narration { text, .. } => {
+                        self.draw_block(&mut cursor, text, profile.columns.narration, profile.left_margin);
+                    }
+                    ScriptElement::Action { text, .. } => {
+                        let action_text = format!("({})", text);
+                        self.draw_block(&mut cursor, &action_text, profile.columns.action, profile.left_margin);
+                    }
+                    ScriptElement::Transition { text, .. } => {
+                        // Transitions are set flush against the right margin
+                        let transition = text.to_uppercase();
+                        let right_edge = profile.page_width - profile.right_margin;
+                        cursor.ensure_lines(1);
+                        cursor.text(&transition, right_edge - profile.text_width(&transition));
+                        cursor.advance(1);
+                    }
+                }
+                cursor.blank_line();
+            }
+        }
+
+        doc.save(&mut BufWriter::new(out))?;
+        Ok(())
+    }
+}
+
+impl PdfRenderer {
+    /// Lays out a standalone title page: the title, credit and author centred a
+    /// third of the way down, with contact details at the bottom left and the
+    /// draft date and copyright notice at the bottom right.
+    fn draw_title_page(&self, cursor: &mut PageCursor, title_page: &TitlePage) {
+        let profile = cursor.profile;
+        let right_edge = profile.page_width - profile.right_margin;
+
+        cursor.move_to_line(profile.lines_per_page / 3);
+        cursor.centered_text(&title_page.title.to_uppercase());
+        cursor.advance(1);
+
+        for value in [&title_page.credit, &title_page.author].into_iter().flatten() {
+            cursor.advance(1);
+            for line in value.lines() {
+                cursor.centered_text(line);
+                cursor.advance(1);
+            }
+        }
+
+        if !title_page.notes.is_empty() {
+            cursor.advance(1);
+            for note in &title_page.notes {
+                for line in layout::wrap(note, profile.columns.action) {
+                    cursor.centered_text(&line);
+                    cursor.advance(1);
+                }
+            }
+        }
+
+        if let Some(contact) = &title_page.contact {
+            let lines: Vec<&str> = contact.lines().collect();
+            cursor.move_to_line(profile.lines_per_page.saturating_sub(lines.len()));
+            for line in lines {
+                cursor.text(line, profile.left_margin);
+                cursor.advance(1);
+            }
+        }
+
+        let footer: Vec<&str> = [&title_page.draft_date, &title_page.copyright]
+            .into_iter()
+            .flatten()
+            .flat_map(|value| value.lines())
+            .collect();
+        cursor.move_to_line(profile.lines_per_page.saturating_sub(footer.len()));
+        for line in footer {
+            cursor.text(line, right_edge - profile.text_width(line));
+            cursor.advance(1);
+        }
+    }
+
+    /// Draws the character cue and the dialogue text under it, interrupting
+    /// the text with each parenthetical at the position where it occurred in
+    /// the speech.
+    fn draw_speech(&self, cursor: &mut PageCursor, speaker: &str, text: &str, actions: &[Parenthetical]) {
+        let profile = cursor.profile;
+
+        // The character cue always stays on the same page as its first line
+        cursor.ensure_lines(2);
+        cursor.text(&speaker.to_uppercase(), profile.character_indent);
+        cursor.advance(1);
+
+        for part in dialogue_parts(text, actions) {
+            match part {
+                DialoguePart::Speech(speech) => {
+                    self.draw_block(cursor, speech, profile.columns.dialogue, profile.dialogue_indent);
+                }
+                DialoguePart::Parenthetical(wryly) => {
+                    let wryly = format!("({})", wryly);
+                    self.draw_block(cursor, &wryly, profile.columns.parenthetical, profile.parenthetical_indent);
+                }
+            }
+        }
+    }
+
+    /// Wraps `text` to `width` character cells and draws one line per row,
+    /// breaking onto a new page wherever the block crosses the bottom margin.
+    /// Paragraphs, separated by newlines, are set apart by an empty line.
+    fn draw_block(&self, cursor: &mut PageCursor, text: &str, width: usize, x: f32) {
+        for (index, paragraph) in text.split('\n').enumerate() {
+            if index > 0 {
+                cursor.blank_line();
+            }
+            for line in layout::wrap(paragraph, width) {
+                cursor.ensure_lines(1);
+                cursor.text(&line, x);
+                cursor.advance(1);
+            }
+        }
+    }
+}
+
+/// Tracks the current page and line while rendering, adding new pages to the
+/// document whenever the content reaches the profile's lines-per-page limit.
+struct PageCursor<'a> {
+    doc: &'a PdfDocumentReference,
+    profile: &'a LayoutProfile,
+    font: &'a IndirectFontRef,
+    layer: PdfLayerReference,
+    page: usize,
+    line: usize,
+}
+
+impl<'a> PageCursor<'a> {
+    fn new(
+        doc: &'a PdfDocumentReference,
+        profile: &'a LayoutProfile,
+        font: &'a IndirectFontRef,
+        layer: PdfLayerReference,
+    ) -> Self {
+        PageCursor {
+            doc,
+            profile,
+            font,
+            layer,
+            page: 1,
+            line: 0,
+        }
+    }
+
+    /// Starts a new page unless `lines` more lines fit on the current one.
+    fn ensure_lines(&mut self, lines: usize) {
+        if self.line > 0 && self.line + lines > self.profile.lines_per_page {
+            self.new_page();
+        }
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(
+            Mm(self.profile.page_width),
+            Mm(self.profile.page_height),
+            "Layer 1",
+        );
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.page += 1;
+        self.line = 0;
+
+        // By convention the first page of a screenplay is not numbered
+        if self.profile.page_numbers && self.page > 1 {
+            let number = format!("{}.", self.page);
+            let x = self.profile.page_width - self.profile.right_margin - self.profile.text_width(&number);
+            let y = self.profile.page_height - self.profile.page_number_top;
+            self.layer.use_text(number, self.profile.font_size, Mm(x), Mm(y), self.font);
+        }
+    }
+
+    /// Moves the remaining content onto a fresh page numbered 1, so that a
+    /// title page does not count towards the script's page numbers.
+    fn restart_numbering(&mut self) {
+        self.page = 0;
+        self.new_page();
+    }
+
+    fn move_to_line(&mut self, line: usize) {
+        self.line = line;
+    }
+
+    fn centered_text(&self, text: &str) {
+        let x = (self.profile.page_width - self.profile.text_width(text)) / 2.0;
+        self.text(text, x);
+    }
+
+    fn text(&self, text: &str, x: f32) {
+        let y = self.profile.page_height
+            - self.profile.top_margin
+            - (self.line + 1) as f32 * self.profile.line_height;
+        self.layer.use_text(text, self.profile.font_size, Mm(x), Mm(y), self.font);
+    }
+
+    fn advance(&mut self, lines: usize) {
+        self.line += lines;
+    }
+
+    /// Leaves an empty line between blocks, except at the top of a page.
+    fn blank_line(&mut self) {
+        if self.line > 0 {
+            self.advance(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `test` with a cursor at the top of the first page of a new
+    /// document in the spec layout.
+    fn with_cursor(test: impl FnOnce(&mut PageCursor)) {
+        let profile = LayoutProfile::spec_screenplay();
+        let (doc, page, layer) = PdfDocument::new("Test", Mm(profile.page_width), Mm(profile.page_height), "Layer 1");
+        let font = doc.add_builtin_font(BuiltinFont::Courier).unwrap();
+        let mut cursor = PageCursor::new(&doc, &profile, &font, doc.get_page(page).get_layer(layer));
+        test(&mut cursor);
+    }
+
+    #[test]
+    fn block_crossing_the_bottom_margin_continues_on_the_next_page() {
+        with_cursor(|cursor| {
+            cursor.move_to_line(50);
+            // Ten words of four cells wrap to ten lines of five
+            PdfRenderer.draw_block(cursor, &["word"; 10].join(" "), 5, 0.0);
+            assert_eq!((cursor.page, cursor.line), (2, 5));
+        });
+    }
+
+    #[test]
+    fn character_cue_stays_with_its_first_line() {
+        with_cursor(|cursor| {
+            cursor.move_to_line(54);
+            PdfRenderer.draw_speech(cursor, "ALICE", "Hello.", &[]);
+            assert_eq!((cursor.page, cursor.line), (2, 2));
+        });
+        with_cursor(|cursor| {
+            cursor.move_to_line(53);
+            PdfRenderer.draw_speech(cursor, "ALICE", "Hello.", &[]);
+            assert_eq!((cursor.page, cursor.line), (1, 55));
+        });
+    }
+
+    #[test]
+    fn ensure_lines_never_leaves_a_page_empty() {
+        with_cursor(|cursor| {
+            cursor.ensure_lines(100);
+            assert_eq!((cursor.page, cursor.line), (1, 0));
+        });
+    }
+}
@@ -1,86 +1,168 @@
-use crate::ast::{Script, ScriptElement};
-use printpdf::*;
+use crate::ast::{Parenthetical, Script};
+use crate::layout::{ColumnWidths, LayoutProfile};
+use crate::fdx::FdxRenderer;
+use crate::fountain::FountainRenderer;
+use crate::html::HtmlRenderer;
+use crate::latex::LatexRenderer;
+use crate::markdown::MarkdownRenderer;
+use crate::pdf::PdfRenderer;
 use anyhow::Result;
-use std::fs::File;
-use std::io::BufWriter;
+use std::io::Write;
 
-pub struct PdfRenderer {
-    font_size: f32,
-    line_height: f32,
+/// An output backend that turns a parsed script into a document.
+pub trait Renderer {
+    /// Name used to select the backend, e.g. with `--format`.
+    fn name(&self) -> &'static str;
+
+    /// Extension of the files this backend writes, without the dot.
+    fn file_extension(&self) -> &'static str;
+
+    fn render(&self, script: &Script, out: &mut dyn Write, options: &RenderOptions) -> Result<()>;
+}
+
+/// Settings shared by every renderer. Backends ignore the ones that do not
+/// apply to them, such as page geometry for text formats.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderOptions {
+    pub profile: LayoutProfile,
+}
+
+/// A run of a speech, in the order it is delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialoguePart<'a> {
+    Speech(&'a str),
+    Parenthetical(&'a str),
 }
 
-impl PdfRenderer {
+/// The output backends available to the command-line tool, keyed by name.
+pub struct RendererRegistry {
+    renderers: Vec<Box<dyn Renderer>>,
+}
+
+impl RenderOptions {
     pub fn new() -> Self {
-        PdfRenderer {
-            font_size: 12.0,
-            line_height: 14.0,
-        }
+        Self::default()
     }
-    
-    pub fn render(&self, script: &Script, output_path: &str) -> Result<()> {
-        let (doc, page1, layer1) = PdfDocument::new("Script", Mm(210.0), Mm(297.0), "Layer 1");
-        let font = doc.add_builtin_font(BuiltinFont::Courier)?;
-        
-        let current_layer = doc.get_page(page1).get_layer(layer1);
-        
-        let mut y_position = 250.0;
-        
-        // Title section
-        if !script.title_section.is_empty() {
-            current_layer.use_text(&script.title_section, self.font_size, Mm(20.0), Mm(y_position), &font);
-            y_position -= self.line_height * 2.0;
-        }
-        
-        // Characters section
-        if !script.characters.is_empty() {
-            current_layer.use_text("CHARACTERS:", self.font_size, Mm(20.0), Mm(y_position), &font);
-            y_position -= self.line_height;
-            
-            for (code, name) in &script.characters {
-                let char_line = format!("{}: {}", code, name);
-                current_layer.use_text(&char_line, self.font_size, Mm(25.0), Mm(y_position), &font);
-                y_position -= self.line_height;
-            }
-            y_position -= self.line_height;
+
+    pub fn with_profile(mut self, profile: LayoutProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_column_widths(mut self, column_widths: ColumnWidths) -> Self {
+        self.profile.columns = column_widths;
+        self
+    }
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl RendererRegistry {
+    pub fn new() -> Self {
+        RendererRegistry { renderers: Vec::new() }
+    }
+
+    /// All renderers that ship with the tool.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(PdfRenderer::new()));
+        registry.register(Box::new(HtmlRenderer::new()));
+        registry.register(Box::new(LatexRenderer::new()));
+        registry.register(Box::new(FountainRenderer::new()));
+        registry.register(Box::new(MarkdownRenderer::new()));
+        registry.register(Box::new(FdxRenderer::new()));
+        registry
+    }
+
+    /// Adds a renderer, replacing any registered under the same name.
+    pub fn register(&mut self, renderer: Box<dyn Renderer>) {
+        self.renderers.retain(|existing| existing.name() != renderer.name());
+        self.renderers.push(renderer);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Renderer> {
+        self.renderers
+            .iter()
+            .find(|renderer| renderer.name() == name)
+            .map(|renderer| renderer.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.renderers.iter().map(|renderer| renderer.name())
+    }
+}
+
+/// Splits dialogue `text` around its parentheticals, so each wryly can be set
+/// at the point of the speech where it occurred. A speech without any text
+/// still yields one, empty, `Speech` part when it has no parentheticals.
+pub fn dialogue_parts<'a>(text: &'a str, actions: &'a [Parenthetical]) -> Vec<DialoguePart<'a>> {
+    let mut parts = Vec::new();
+    let mut position = 0;
+
+    for action in actions {
+        let offset = action.position_in(text, position);
+        let before = text[position..offset].trim();
+        if !before.is_empty() {
+            parts.push(DialoguePart::Speech(before));
         }
-        
-        // Script content
-        for scene in &script.scenes {
-            if let Some(location) = &scene.location {
-                let location_text = format!("[{}]", location);
-                current_layer.use_text(&location_text, self.font_size, Mm(20.0), Mm(y_position), &font);
-                y_position -= self.line_height * 1.5;
-            }
-            
-            for element in &scene.elements {
-                match element {
-                    ScriptElement::Dialogue { speaker, text, .. } => {
-                        let speaker_text = format!("{}:", speaker);
-                        current_layer.use_text(&speaker_text, self.font_size, Mm(20.0), Mm(y_position), &font);
-                        y_position -= self.line_height;
-                        
-                        current_layer.use_text(text, self.font_size, Mm(25.0), Mm(y_position), &font);
-                        y_position -= self.line_height * 1.5;
-                    }
-                    ScriptElement::Narration(text) => {
-                        current_layer.use_text(text, self.font_size, Mm(20.0), Mm(y_position), &font);
-                        y_position -= self.line_height * 1.5;
-                    }
-                    ScriptElement::Action(text) => {
-                        let action_text = format!("({})", text);
-                        current_layer.use_text(&action_text, self.font_size, Mm(30.0), Mm(y_position), &font);
-                        y_position -= self.line_height;
-                    }
-                }
-                
-                // Simple page break check
-                if y_position < 30.0 {
-                    break;
-                }
-            }
+        parts.push(DialoguePart::Parenthetical(&action.text));
+        position = offset;
+    }
+
+    let rest = text[position..].trim();
+    if !rest.is_empty() || actions.is_empty() {
+        parts.push(DialoguePart::Speech(rest));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Span;
+    use DialoguePart::{Parenthetical as Wryly, Speech};
+
+    fn parenthetical(text: &str, offset: usize) -> Parenthetical {
+        Parenthetical {
+            text: text.to_string(),
+            offset,
+            span: Span::default(),
         }
-        
-        doc.save(&mut BufWriter::new(File::create(output_path)?))?;
-        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn speech_is_split_around_parentheticals() {
+        let actions = [parenthetical("quietly", 0), parenthetical("beat", 7)];
+        assert_eq!(
+            dialogue_parts("Ça va bien", &actions),
+            vec![Wryly("quietly"), Speech("Ça va"), Wryly("beat"), Speech("bien")]
+        );
+    }
+
+    #[test]
+    fn offsets_inside_a_character_are_moved_back() {
+        let actions = [parenthetical("beat", 1)];
+        assert_eq!(dialogue_parts("Ça va", &actions), vec![Wryly("beat"), Speech("Ça va")]);
+        let actions = [parenthetical("beat", 3)];
+        assert_eq!(dialogue_parts("ééé", &actions), vec![Speech("é"), Wryly("beat"), Speech("éé")]);
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_clamped() {
+        let actions = [parenthetical("beat", 4), parenthetical("sighs", 2), parenthetical("laughs", 99)];
+        assert_eq!(
+            dialogue_parts("Non, non", &actions),
+            vec![Speech("Non,"), Wryly("beat"), Wryly("sighs"), Speech("non"), Wryly("laughs")]
+        );
+    }
+
+    #[test]
+    fn empty_speech_without_parentheticals_is_kept() {
+        assert_eq!(dialogue_parts("", &[]), vec![Speech("")]);
+        assert_eq!(dialogue_parts("", &[parenthetical("beat", 0)]), vec![Wryly("beat")]);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::ast::Span;
+use crate::diagnostic::{Diagnostic, Severity, ValidationReport};
+use crate::rules::RuleRegistry;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// How validation results are written by the command-line tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "human" => Ok(ReportFormat::Human),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => Err(anyhow!("Unknown report format '{}'", s)),
+        }
+    }
+}
+
+/// Serializes a report as a JSON document with summary counts and every
+/// diagnostic, including its span, suggestion and related locations.
+pub fn to_json(report: &ValidationReport, file_name: &str) -> Result<String> {
+    let document = json!({
+        "file": file_name,
+        "errors": report.errors().count(),
+        "warnings": report.warnings().count(),
+        "diagnostics": report.diagnostics(),
+    });
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Serializes a report as a SARIF 2.1.0 log, as consumed by code scanning and
+/// code review tools.
+pub fn to_sarif(report: &ValidationReport, file_name: &str) -> Result<String> {
+    let registry = RuleRegistry::builtin();
+    let rules: BTreeSet<&str> = report.diagnostics().iter().map(|diagnostic| diagnostic.code).collect();
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|id| match registry.get(id) {
+            Some(rule) => json!({ "id": id, "shortDescription": { "text": rule.description() } }),
+            None => json!({ "id": id }),
+        })
+        .collect();
+    let results: Vec<Value> = report
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| sarif_result(diagnostic, file_name))
+        .collect();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+fn sarif_result(diagnostic: &Diagnostic, file_name: &str) -> Value {
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let mut text = diagnostic.message.clone();
+    if let Some(suggestion) = &diagnostic.suggestion {
+        text = format!("{}\nSuggestion: {}", text, suggestion);
+    }
+
+    let mut result = json!({
+        "ruleId": diagnostic.code,
+        "level": level,
+        "message": { "text": text },
+    });
+
+    if let Some(span) = &diagnostic.span {
+        result["locations"] = json!([{ "physicalLocation": sarif_location(span, file_name) }]);
+    }
+
+    if !diagnostic.related.is_empty() {
+        result["relatedLocations"] = diagnostic
+            .related
+            .iter()
+            .enumerate()
+            .map(|(index, related)| {
+                json!({
+                    "id": index,
+                    "physicalLocation": sarif_location(&related.span, file_name),
+                    "message": { "text": related.message },
+                })
+            })
+            .collect();
+    }
+
+    if let Some(suggestion) = &diagnostic.suggestion {
+        result["properties"] = json!({ "suggestion": suggestion.message });
+
+        if suggestion.is_fixable() {
+            let replacements: Vec<Value> = suggestion
+                .edits
+                .iter()
+                .map(|edit| {
+                    json!({
+                        "deletedRegion": {
+                            "byteOffset": edit.byte_range.start,
+                            "byteLength": edit.byte_range.len(),
+                        },
+                        "insertedContent": { "text": edit.replacement },
+                    })
+                })
+                .collect();
+            result["fixes"] = json!([{
+                "description": { "text": suggestion.message },
+                "artifactChanges": [{
+                    "artifactLocation": { "uri": artifact_uri(file_name) },
+                    "replacements": replacements,
+                }],
+            }]);
+        }
+    }
+
+    result
+}
+
+fn sarif_location(span: &Span, file_name: &str) -> Value {
+    let mut location = json!({ "artifactLocation": { "uri": artifact_uri(file_name) } });
+    // A hand-written AST may leave spans out, and SARIF lines start at 1
+    if span.line > 0 {
+        location["region"] = json!({
+            "startLine": span.line,
+            "startColumn": span.column,
+        });
+    }
+    location
+}
+
+/// The URI SARIF expects for `file_name`: a percent-encoded relative
+/// reference, or a `file://` URI for an absolute path.
+fn artifact_uri(file_name: &str) -> String {
+    let path = file_name.replace('\\', "/");
+    if !Path::new(file_name).is_absolute() {
+        return percent_encode(&path, "/");
+    }
+    // Windows paths such as `C:/scripts` need a slash before the drive
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    format!("file://{}", percent_encode(&path, "/:"))
+}
+
+/// Encodes every byte of `text` except unreserved characters and `keep`.
+fn percent_encode(text: &str, keep: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) || keep.as_bytes().contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
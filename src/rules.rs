@@ -0,0 +1,850 @@
+use crate::ast::{Script, ScriptElement, Span};
+use crate::diagnostic::{Diagnostic, Severity, Suggestion, TextEdit};
+use crate::grammar::CodeGrammar;
+use crate::parser::NarratorCodes;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the per-project configuration, looked up next to the script
+/// and in its parent directories.
+pub const CONFIG_FILE_NAME: &str = "script-parser.toml";
+
+/// A single validation check. Every rule has a stable ID that is used in
+/// diagnostics, configuration files and suppression comments.
+pub trait Rule {
+    fn id(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity;
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>);
+
+    /// Starts a diagnostic for this rule at its default severity.
+    fn diagnostic(&self, message: String) -> Diagnostic {
+        Diagnostic::new(self.default_severity(), self.id(), message)
+    }
+}
+
+/// The set of rules the validator runs, in order.
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+/// Configured level for a rule, overriding its default severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+/// Per-project validator configuration, usually read from `script-parser.toml`:
+///
+/// ```toml
+/// [rules]
+/// unused-character = "off"
+/// reserved-code = "error"
+///
+/// [character-codes]
+/// spaces = false
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorConfig {
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+    /// Grammar for character codes, shared by the lexer and the
+    /// `invalid-character-code` rule.
+    #[serde(default, rename = "character-codes")]
+    pub character_codes: CodeGrammar,
+}
+
+impl Default for RuleRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        RuleRegistry { rules: Vec::new() }
+    }
+
+    /// All rules that ship with the validator.
+    pub fn builtin() -> Self {
+        Self::builtin_with_grammar(CodeGrammar::default())
+    }
+
+    /// The built-in rules, checking character codes against `grammar`.
+    pub fn builtin_with_grammar(grammar: CodeGrammar) -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(MissingTitle));
+        registry.register(Box::new(EmptyTitle));
+        registry.register(Box::new(MissingCharacters));
+        registry.register(Box::new(MissingScript));
+        registry.register(Box::new(ReservedCode::default()));
+        registry.register(Box::new(EmptyCharacterCode));
+        registry.register(Box::new(InvalidCharacterCode { grammar }));
+        registry.register(Box::new(EmptyCharacterName));
+        registry.register(Box::new(DuplicateCharacter));
+        registry.register(Box::new(UndefinedSpeaker::default()));
+        registry.register(Box::new(EmptyDialogue));
+        registry.register(Box::new(EmptyParenthetical));
+        registry.register(Box::new(EmptyNarration));
+        registry.register(Box::new(EmptyAction));
+        registry.register(Box::new(EmptyScene));
+        registry.register(Box::new(EmptyLocation));
+        registry.register(Box::new(UnusedCharacter));
+        registry
+    }
+
+    pub fn register(&mut self, rule: Box<dyn Rule>) {
+        self.rules.push(rule);
+    }
+
+    /// Replaces the registered rule with the same id as `rule`, or registers
+    /// it if there is none.
+    pub fn replace(&mut self, rule: Box<dyn Rule>) {
+        match self.rules.iter_mut().find(|registered| registered.id() == rule.id()) {
+            Some(registered) => *registered = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Rule> {
+        self.rules.iter().find(|rule| rule.id() == id).map(|rule| rule.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+}
+
+impl ValidatorConfig {
+    pub fn from_toml(source: &str) -> Result<Self> {
+        Ok(toml::from_str(source)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::from_toml(&source).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Finds the nearest `script-parser.toml` in `start` or its ancestors.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// The configured level for `rule`, or `None` to use its default severity.
+    pub fn level(&self, rule: &str) -> Option<RuleLevel> {
+        self.rules.get(rule).copied()
+    }
+
+    /// Fails if the configuration names rules that are not in `registry`.
+    pub fn check_rules(&self, registry: &RuleRegistry) -> Result<()> {
+        let unknown: Vec<&str> = self
+            .rules
+            .keys()
+            .map(String::as_str)
+            .filter(|id| registry.get(id).is_none())
+            .collect();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Unknown rule(s) in configuration: {}", unknown.join(", ")))
+        }
+    }
+}
+
+pub struct MissingTitle;
+
+impl Rule for MissingTitle {
+    fn id(&self) -> &'static str {
+        "missing-title"
+    }
+
+    fn description(&self) -> &'static str {
+        "The script has a title section"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        if script.title_page.is_none() {
+            diagnostics.push(
+                self.diagnostic("Title section is missing".to_string())
+                    .with_suggestion("Add a title section with '# Title' header".to_string())
+            );
+        }
+    }
+}
+
+pub struct EmptyTitle;
+
+impl Rule for EmptyTitle {
+    fn id(&self) -> &'static str {
+        "empty-title"
+    }
+
+    fn description(&self) -> &'static str {
+        "The title section names the script"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        if let Some(title_page) = &script.title_page
+            && title_page.title.trim().is_empty()
+        {
+            diagnostics.push(
+                self.diagnostic("Script title is empty".to_string())
+                    .with_span(&title_page.span)
+                    .with_suggestion("Add the title after the '#' or in a 'Title:' line".to_string())
+            );
+        }
+    }
+}
+
+pub struct MissingCharacters;
+
+impl Rule for MissingCharacters {
+    fn id(&self) -> &'static str {
+        "missing-characters"
+    }
+
+    fn description(&self) -> &'static str {
+        "The script defines at least one character"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        if script.characters.is_empty() {
+            diagnostics.push(
+                self.diagnostic("Character definitions are missing".to_string())
+                    .with_suggestion("Add character definitions in the '## Characters' section".to_string())
+            );
+        }
+    }
+}
+
+pub struct MissingScript;
+
+impl Rule for MissingScript {
+    fn id(&self) -> &'static str {
+        "missing-script"
+    }
+
+    fn description(&self) -> &'static str {
+        "The script has content in its '## Script' section"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        if script.scenes.is_empty() {
+            diagnostics.push(
+                self.diagnostic("Script content is missing".to_string())
+                    .with_suggestion("Add script content in the '## Script' section".to_string())
+            );
+        }
+    }
+}
+
+/// Reports characters using a narrator code: `N`, and any code passed with
+/// `--narrator`.
+#[derive(Default)]
+pub struct ReservedCode {
+    pub codes: NarratorCodes,
+}
+
+impl Rule for ReservedCode {
+    fn id(&self) -> &'static str {
+        "reserved-code"
+    }
+
+    fn description(&self) -> &'static str {
+        "Characters do not use codes reserved for the narrator"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for keyword in self.codes.iter() {
+            if let Some(character) = script.characters.get(keyword) {
+                diagnostics.push(
+                    self.diagnostic(format!("Character code '{}' is reserved for narrator", keyword))
+                        .with_span(&character.span)
+                        .with_suggestion("Consider using a different code for this character".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyCharacterCode;
+
+impl Rule for EmptyCharacterCode {
+    fn id(&self) -> &'static str {
+        "empty-character-code"
+    }
+
+    fn description(&self) -> &'static str {
+        "Every character definition has a code"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for character in &script.characters {
+            if character.code.is_empty() {
+                diagnostics.push(
+                    self.diagnostic("Character code cannot be empty".to_string())
+                        .with_span(&character.span)
+                        .with_suggestion("Provide a valid character code".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct InvalidCharacterCode {
+    pub grammar: CodeGrammar,
+}
+
+impl Rule for InvalidCharacterCode {
+    fn id(&self) -> &'static str {
+        "invalid-character-code"
+    }
+
+    fn description(&self) -> &'static str {
+        "Character codes follow the configured character-code grammar"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for character in &script.characters {
+            if !character.code.is_empty() && !self.grammar.is_valid(&character.code) {
+                let message = format!(
+                    "Invalid character code '{}': must start with an uppercase letter and contain only {}",
+                    character.code,
+                    self.grammar.describe()
+                );
+                diagnostics.push(
+                    self.diagnostic(message)
+                        .with_span(&character.span)
+                        .with_suggestion(format!("Use only {} for character codes", self.grammar.describe()))
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyCharacterName;
+
+impl Rule for EmptyCharacterName {
+    fn id(&self) -> &'static str {
+        "empty-character-name"
+    }
+
+    fn description(&self) -> &'static str {
+        "Every character definition has a name"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for character in &script.characters {
+            if character.name.trim().is_empty() {
+                diagnostics.push(
+                    self.diagnostic(format!("Character name for code '{}' cannot be empty", character.code))
+                        .with_span(&character.span)
+                        .with_suggestion("Provide a valid character name".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct DuplicateCharacter;
+
+impl Rule for DuplicateCharacter {
+    fn id(&self) -> &'static str {
+        "duplicate-character"
+    }
+
+    fn description(&self) -> &'static str {
+        "Each character code is defined only once"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for duplicate in script.characters.duplicates() {
+            let Some(original) = script.characters.get(&duplicate.code) else {
+                continue;
+            };
+            let (message, suggestion) = if duplicate.name == original.name {
+                (
+                    format!("Duplicate character code '{}'", duplicate.code),
+                    deletion("Remove the repeated definition", &duplicate.span),
+                )
+            } else {
+                (
+                    format!(
+                        "Conflicting definitions for character code '{}': '{}' and '{}'",
+                        duplicate.code, original.name, duplicate.name
+                    ),
+                    Suggestion::new("Use unique codes for each character".to_string()),
+                )
+            };
+            diagnostics.push(
+                self.diagnostic(message)
+                    .with_span(&duplicate.span)
+                    .with_related(&original.span, format!("'{}' first defined here", original.code))
+                    .with_fix(suggestion)
+            );
+        }
+    }
+}
+
+/// Reports dialogue spoken by a code that is neither a defined character nor
+/// one of the narrator codes.
+#[derive(Default)]
+pub struct UndefinedSpeaker {
+    pub narrator_codes: NarratorCodes,
+}
+
+impl Rule for UndefinedSpeaker {
+    fn id(&self) -> &'static str {
+        "undefined-speaker"
+    }
+
+    fn description(&self) -> &'static str {
+        "Dialogue is spoken by a defined character code or alias"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for element in elements(script) {
+            if let ScriptElement::Dialogue { speaker, span, .. } = element
+                && script.characters.resolve(speaker).is_none()
+                && !self.narrator_codes.contains(speaker)
+            {
+                let diagnostic = self
+                    .diagnostic(format!("Undefined character code '{}' used in dialogue", speaker))
+                    .with_span(span);
+
+                // Most undefined speakers are typos of a defined character
+                let diagnostic = match closest_character(script, speaker) {
+                    Some((code, name)) => {
+                        let message = if code == name {
+                            format!("Did you mean '{}'?", code)
+                        } else {
+                            format!("Did you mean '{}' ({})?", code, name)
+                        };
+                        // The line starts with the speaker code
+                        let start = span.byte_range.start;
+                        diagnostic.with_fix(
+                            Suggestion::new(message)
+                                .with_edit(TextEdit::new(start..start + speaker.len(), code.to_string()))
+                        )
+                    }
+                    None => diagnostic
+                        .with_suggestion(format!("Add '{}: Character Name' to the character definitions", speaker)),
+                };
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+}
+
+/// Finds the defined character whose code, alias or name is closest to
+/// `speaker`, returning the code or alias to use instead and the character's
+/// name. Candidates further than a third of their length away are ignored.
+fn closest_character<'a>(script: &'a Script, speaker: &str) -> Option<(&'a str, &'a str)> {
+    let speaker = speaker.to_uppercase();
+    let mut best: Option<(usize, &str, &str)> = None;
+
+    for character in &script.characters {
+        let candidates = std::iter::once((character.code.as_str(), character.code.to_uppercase()))
+            .chain(character.aliases.iter().map(|alias| (alias.as_str(), alias.to_uppercase())))
+            .chain(std::iter::once((character.code.as_str(), character.name.to_uppercase())));
+
+        for (replacement, candidate) in candidates {
+            let distance = edit_distance(&speaker, &candidate);
+            let limit = (candidate.chars().count() / 3).max(1);
+            if distance <= limit && best.is_none_or(|(best_distance, _, _)| distance < best_distance) {
+                best = Some((distance, replacement, character.name.as_str()));
+            }
+        }
+    }
+
+    best.map(|(_, replacement, name)| (replacement, name))
+}
+
+/// Optimal string alignment distance: the number of single-character
+/// insertions, deletions, substitutions and adjacent transpositions needed to
+/// turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+pub struct EmptyDialogue;
+
+impl Rule for EmptyDialogue {
+    fn id(&self) -> &'static str {
+        "empty-dialogue"
+    }
+
+    fn description(&self) -> &'static str {
+        "Dialogue lines have text"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for element in elements(script) {
+            if let ScriptElement::Dialogue { speaker, text, span, .. } = element
+                && text.trim().is_empty()
+            {
+                diagnostics.push(
+                    self.diagnostic(format!("Empty dialogue for character '{}'", speaker))
+                        .with_span(span)
+                        .with_suggestion("Provide dialogue text or remove the line".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyParenthetical;
+
+impl Rule for EmptyParenthetical {
+    fn id(&self) -> &'static str {
+        "empty-parenthetical"
+    }
+
+    fn description(&self) -> &'static str {
+        "Parentheticals within dialogue have text"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for element in elements(script) {
+            let ScriptElement::Dialogue { actions, .. } = element else {
+                continue;
+            };
+            for action in actions {
+                if action.text.trim().is_empty() {
+                    diagnostics.push(
+                        self.diagnostic("Empty action description".to_string())
+                            .with_span(&action.span)
+                            .with_suggestion("Provide action text or remove the action".to_string())
+                    );
+                }
+            }
+        }
+    }
+}
+
+pub struct EmptyNarration;
+
+impl Rule for EmptyNarration {
+    fn id(&self) -> &'static str {
+        "empty-narration"
+    }
+
+    fn description(&self) -> &'static str {
+        "Narration lines have text"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for element in elements(script) {
+            if let ScriptElement::Narration { text, span, .. } = element
+                && text.trim().is_empty()
+            {
+                diagnostics.push(
+                    self.diagnostic("Empty narration text".to_string())
+                        .with_span(span)
+                        .with_fix(deletion("Provide narration text or remove the line", span))
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyAction;
+
+impl Rule for EmptyAction {
+    fn id(&self) -> &'static str {
+        "empty-action"
+    }
+
+    fn description(&self) -> &'static str {
+        "Action lines have text"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for element in elements(script) {
+            if let ScriptElement::Action { text, span } = element
+                && text.trim().is_empty()
+            {
+                diagnostics.push(
+                    self.diagnostic("Empty action text".to_string())
+                        .with_span(span)
+                        .with_suggestion("Provide action text or remove the line".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyScene;
+
+impl Rule for EmptyScene {
+    fn id(&self) -> &'static str {
+        "empty-scene"
+    }
+
+    fn description(&self) -> &'static str {
+        "Every scene has some content"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for (scene_index, scene) in script.scenes.iter().enumerate() {
+            if scene.elements.is_empty() {
+                diagnostics.push(
+                    self.diagnostic(format!("Scene {} has no content", scene_index + 1))
+                        .with_span(&scene.span)
+                        .with_fix(deletion("Add dialogue, narration, or action to the scene, or remove it", &scene.span))
+                );
+            }
+        }
+    }
+}
+
+pub struct EmptyLocation;
+
+impl Rule for EmptyLocation {
+    fn id(&self) -> &'static str {
+        "empty-location"
+    }
+
+    fn description(&self) -> &'static str {
+        "Scene headings name a location"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for scene in &script.scenes {
+            if let Some(location) = &scene.location
+                && location.trim().is_empty()
+            {
+                diagnostics.push(
+                    self.diagnostic("Scene location cannot be empty".to_string())
+                        .with_span(&scene.span)
+                        .with_suggestion("Provide a valid location name".to_string())
+                );
+            }
+        }
+    }
+}
+
+pub struct UnusedCharacter;
+
+impl Rule for UnusedCharacter {
+    fn id(&self) -> &'static str {
+        "unused-character"
+    }
+
+    fn description(&self) -> &'static str {
+        "Every defined character speaks at least once"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        let mut used_characters: HashSet<&str> = HashSet::new();
+        for element in elements(script) {
+            match element {
+                ScriptElement::Dialogue { speaker, .. } => {
+                    // A mistyped speaker counts as a use of the character that
+                    // `undefined-speaker` would rename it to, so that fixing
+                    // both does not delete the definition
+                    let character = script.characters.resolve(speaker).or_else(|| {
+                        closest_character(script, speaker).and_then(|(code, _)| script.characters.resolve(code))
+                    });
+                    used_characters.insert(character.map_or(speaker, |character| &character.code));
+                }
+                ScriptElement::Narration { voice: Some(voice), .. } => {
+                    used_characters.insert(voice);
+                }
+                _ => {}
+            }
+        }
+
+        for character in &script.characters {
+            if !used_characters.contains(character.code.as_str()) {
+                diagnostics.push(
+                    self.diagnostic(format!("Character '{}' ({}) is defined but never used", character.name, character.code))
+                        .with_span(&character.span)
+                        .with_fix(deletion("Remove unused character or add dialogue for this character", &character.span))
+                );
+            }
+        }
+    }
+}
+
+/// A suggestion whose fix deletes the source covered by `span`.
+fn deletion(message: &str, span: &Span) -> Suggestion {
+    Suggestion::new(message.to_string()).with_edit(TextEdit::new(span.byte_range.clone(), String::new()))
+}
+
+/// Every element of every scene, in script order.
+fn elements(script: &Script) -> impl Iterator<Item = &ScriptElement> {
+    script.scenes.iter().flat_map(|scene| &scene.elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Character;
+
+    fn script() -> Script {
+        let mut script = Script::new();
+        for (code, name, aliases) in [
+            ("ALICE", "Alice Johnson", vec![]),
+            ("BOB", "Bob Smith", vec!["BOBBY"]),
+            ("DR_LEE", "Doctor Lee", vec![]),
+            ("JOSÉ", "José Ruiz", vec![]),
+        ] {
+            let mut character = Character::new(code.to_string(), name.to_string(), Span::default());
+            character.aliases = aliases.into_iter().map(String::from).collect();
+            script.characters.insert(character);
+        }
+        script
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("ALICE", "ALICE"), 0);
+        assert_eq!(edit_distance("ALICE", "ALIC"), 1);
+        assert_eq!(edit_distance("ALICE", "ALIICE"), 1);
+        assert_eq!(edit_distance("ALICE", "ALIKE"), 1);
+        assert_eq!(edit_distance("", "BOB"), 3);
+        assert_eq!(edit_distance("KITTEN", "SITTING"), 3);
+    }
+
+    #[test]
+    fn transpositions_count_once() {
+        assert_eq!(edit_distance("ALCIE", "ALICE"), 1);
+        assert_eq!(edit_distance("BBO", "BOB"), 1);
+        // Optimal string alignment edits each substring at most once
+        assert_eq!(edit_distance("CA", "ABC"), 3);
+    }
+
+    #[test]
+    fn edit_distance_works_on_characters() {
+        assert_eq!(edit_distance("JOSE", "JOSÉ"), 1);
+        assert_eq!(edit_distance("JOSÉ", "JSOÉ"), 1);
+    }
+
+    #[test]
+    fn closest_character_matches_codes_aliases_and_names() {
+        let script = script();
+        assert_eq!(closest_character(&script, "ALCIE"), Some(("ALICE", "Alice Johnson")));
+        assert_eq!(closest_character(&script, "alice"), Some(("ALICE", "Alice Johnson")));
+        assert_eq!(closest_character(&script, "BOBBI"), Some(("BOBBY", "Bob Smith")));
+        assert_eq!(closest_character(&script, "DOCTOR LE"), Some(("DR_LEE", "Doctor Lee")));
+        assert_eq!(closest_character(&script, "JOSE"), Some(("JOSÉ", "José Ruiz")));
+    }
+
+    #[test]
+    fn distant_speakers_have_no_match() {
+        let script = script();
+        // Short codes allow a single edit, longer ones one per three characters
+        assert_eq!(closest_character(&script, "BO"), Some(("BOB", "Bob Smith")));
+        assert_eq!(closest_character(&script, "B"), None);
+        assert_eq!(closest_character(&script, "ZED"), None);
+        assert_eq!(closest_character(&script, "ALEX"), None);
+        assert_eq!(closest_character(&Script::new(), "ALICE"), None);
+    }
+}
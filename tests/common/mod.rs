@@ -0,0 +1,42 @@
+//! Helpers shared by the conversion tests.
+// Each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use script_parser::{Lexer, Parser, RenderOptions, Renderer, Scene, Script, ScriptElement, Span};
+
+pub fn parse_markdown(source: &str) -> Script {
+    let tokens = Lexer::new(source.to_string()).tokenize();
+    Parser::new(tokens).parse().unwrap()
+}
+
+pub fn render(renderer: &dyn Renderer, script: &Script) -> String {
+    let mut out = Vec::new();
+    renderer.render(script, &mut out, &RenderOptions::new()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// `scenes` with every span cleared, to compare scripts parsed from
+/// different sources.
+pub fn without_spans(scenes: &[Scene]) -> Vec<Scene> {
+    scenes
+        .iter()
+        .map(|scene| {
+            let mut scene = scene.clone();
+            scene.span = Span::default();
+            for element in &mut scene.elements {
+                match element {
+                    ScriptElement::Dialogue { span, actions, .. } => {
+                        *span = Span::default();
+                        for action in actions {
+                            action.span = Span::default();
+                        }
+                    }
+                    ScriptElement::Narration { span, .. }
+                    | ScriptElement::Action { span, .. }
+                    | ScriptElement::Transition { span, .. } => *span = Span::default(),
+                }
+            }
+            scene
+        })
+        .collect()
+}
@@ -0,0 +1,118 @@
+use script_parser::{Diagnostic, Emitter, Lexer, Parser, Span, Validator};
+
+const SOURCE: &str = "\
+# The Farewell
+
+## Characters
+
+ALICE: Alice Johnson
+BOB: Bob Smith
+BOB: Bob Smith
+
+## Script
+
+[Hall]
+
+JOSÉ: ¡Hola, ALCIE!
+ALCIE: Hi.
+";
+
+/// The first diagnostic reported by `rule` for `SOURCE`.
+fn diagnostic(rule: &str) -> Diagnostic {
+    let script = Parser::new(Lexer::new(SOURCE.to_string()).tokenize()).parse().unwrap();
+    let report = Validator::new().validate(&script);
+    report.diagnostics().iter().find(|diagnostic| diagnostic.code == rule).unwrap().clone()
+}
+
+/// The span of the first occurrence of `text` in `SOURCE`, which is on line 13.
+fn span_of(text: &str) -> Span {
+    let start = SOURCE.find(text).unwrap();
+    let line_start = SOURCE[..start].rfind('\n').unwrap() + 1;
+    Span::new(13, SOURCE[line_start..start].chars().count() + 1, start..start + text.len())
+}
+
+#[test]
+fn reports_quote_the_source() {
+    let rendered = Emitter::new(SOURCE, "farewell.md").render(&diagnostic("duplicate-character"));
+    assert_eq!(
+        rendered,
+        "\
+error[duplicate-character]: Duplicate character code 'BOB'
+ --> farewell.md:7:1
+  |
+7 | BOB: Bob Smith
+  | ^^^^^^^^^^^^^^
+  |
+ ::: farewell.md:6:1
+  |
+6 | BOB: Bob Smith
+  | ^^^^^^^^^^^^^^ 'BOB' first defined here
+  |
+  = help: Remove the repeated definition
+"
+    );
+}
+
+#[test]
+fn gutter_fits_the_widest_line_number() {
+    let rendered = Emitter::new(SOURCE, "farewell.md").render(&diagnostic("undefined-speaker"));
+    assert_eq!(
+        rendered,
+        "\
+error[undefined-speaker]: Undefined character code 'JOSÉ' used in dialogue
+  --> farewell.md:13:1
+   |
+13 | JOSÉ: ¡Hola, ALCIE!
+   | ^^^^^^^^^^^^^^^^^^^
+   |
+   = help: Add 'JOSÉ: Character Name' to the character definitions
+"
+    );
+}
+
+#[test]
+fn carets_are_placed_by_characters() {
+    let diagnostic = Diagnostic::warning("typo", "Misspelled name".to_string()).with_span(&span_of("ALCIE!"));
+    let rendered = Emitter::new(SOURCE, "farewell.md").render(&diagnostic);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines[1], "  --> farewell.md:13:14");
+    assert_eq!(lines[3], "13 | JOSÉ: ¡Hola, ALCIE!");
+    assert_eq!(lines[4], "   |              ^^^^^^");
+}
+
+#[test]
+fn color_is_only_used_when_asked_for() {
+    let diagnostic = diagnostic("duplicate-character");
+    let plain = Emitter::new(SOURCE, "farewell.md").render(&diagnostic);
+    let colored = Emitter::new(SOURCE, "farewell.md").with_color(true).render(&diagnostic);
+
+    assert!(!plain.contains('\x1b'));
+    assert!(colored.starts_with("\x1b[1;31merror[duplicate-character]\x1b[0m"));
+    let stripped = colored
+        .split('\x1b')
+        .enumerate()
+        .map(|(i, part)| if i == 0 { part } else { &part[part.find('m').unwrap() + 1..] })
+        .collect::<String>();
+    assert_eq!(stripped, plain);
+}
+
+#[test]
+fn locations_without_source_are_not_quoted() {
+    let diagnostic = diagnostic("duplicate-character");
+    assert_eq!(
+        Emitter::new("", "farewell.json").render(&diagnostic),
+        "\
+error[duplicate-character]: Duplicate character code 'BOB'
+ --> farewell.json:7:1
+ ::: farewell.json:6:1
+  = help: Remove the repeated definition
+"
+    );
+
+    // Nor are missing spans, which a hand-written AST may leave out
+    let unplaced = Diagnostic::warning("empty-scene", "Scene has no content".to_string()).with_span(&Span::default());
+    assert_eq!(
+        Emitter::new(SOURCE, "farewell.md").render(&unplaced),
+        "warning[empty-scene]: Scene has no content\n"
+    );
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{parse_markdown, render, without_spans};
+use script_parser::{FdxParser, FdxRenderer, Script, ScriptElement, Span, TitlePage};
+use std::fs;
+use std::path::Path;
+
+fn read_fixture(name: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)).unwrap()
+}
+
+fn parse_fdx(source: &str) -> Script {
+    FdxParser::new(source.to_string()).parse().unwrap()
+}
+
+#[test]
+fn markdown_round_trips_through_fdx() {
+    let script = parse_markdown(&read_fixture("fountain/sample.md"));
+    let imported = parse_fdx(&render(&FdxRenderer, &script));
+
+    // FDX keeps the cast list, but not the characters' full names
+    let codes = |script: &Script| -> Vec<String> {
+        script.characters.iter().map(|character| character.code.clone()).collect()
+    };
+    assert_eq!(codes(&imported), codes(&script));
+    assert_eq!(without_spans(&imported.scenes), without_spans(&script.scenes));
+    assert_eq!(
+        imported.title_page.map(|page| TitlePage { span: Span::default(), ..page }),
+        script.title_page.map(|page| TitlePage { span: Span::default(), ..page })
+    );
+}
+
+#[test]
+fn fdx_writer_output_is_stable() {
+    let fdx = render(&FdxRenderer, &parse_fdx(&read_fixture("fdx/brick_and_steel.fdx")));
+    assert_eq!(render(&FdxRenderer, &parse_fdx(&fdx)), fdx);
+}
+
+#[test]
+fn final_draft_document_is_imported() {
+    let script = parse_fdx(&read_fixture("fdx/brick_and_steel.fdx"));
+
+    let title_page = script.title_page.as_ref().unwrap();
+    assert_eq!(title_page.title, "BRICK & STEEL");
+    assert_eq!(title_page.credit.as_deref(), Some("Written by"));
+    assert_eq!(title_page.author.as_deref(), Some("Stu Maschwitz"));
+    assert_eq!(title_page.contact.as_deref(), Some("Stu Maschwitz\nprolost.com"));
+    assert_eq!(title_page.draft_date.as_deref(), Some("First draft"));
+
+    let codes: Vec<&str> = script.characters.iter().map(|character| character.code.as_str()).collect();
+    assert_eq!(codes, vec!["BRICK", "STEEL", "BARTENDER"]);
+
+    assert_eq!(script.scenes.len(), 1);
+    let scene = &script.scenes[0];
+    assert_eq!(scene.location.as_deref(), Some("EXT. BRICK'S PATIO - DAY"));
+    assert_eq!(scene.span.line, 5);
+
+    let summary: Vec<String> = scene
+        .elements
+        .iter()
+        .map(|element| match element {
+            ScriptElement::Dialogue { speaker, text, actions, .. } => {
+                let actions: Vec<&str> = actions.iter().map(|action| action.text.as_str()).collect();
+                format!("{}: {} {:?}", speaker, text, actions)
+            }
+            ScriptElement::Narration { text, .. } => text.clone(),
+            ScriptElement::Transition { text, .. } => format!("> {}", text),
+            other => panic!("unexpected element {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            "A gorgeous day. The sun shines.",
+            "STEEL: Beer's ready! []",
+            "BRICK: Are they cold? [\"shouting\"]",
+            "CLOSE ON the cooler & its contents.",
+            "> CUT TO:",
+        ]
+    );
+}
+
+#[test]
+fn other_xml_is_rejected() {
+    let error = FdxParser::new("<html><body/></html>".to_string()).parse().unwrap_err();
+    assert!(error.to_string().contains("Not a Final Draft document"));
+}
+
+const TITLED: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no" ?>
+<FinalDraft DocumentType="Script" Template="No" Version="4">
+  <Content>
+    <Paragraph Type="Scene Heading"><Text>INT. LIGHTHOUSE - NIGHT</Text></Paragraph>
+    <Paragraph Type="Character"><Text>KEEPER</Text></Paragraph>
+    <Paragraph Type="Parenthetical"><Text>(low)</Text></Paragraph>
+    <Paragraph Type="Dialogue"><Text>Storm's coming.</Text></Paragraph>
+    <Paragraph Type="Character"><Text>VOICE</Text></Paragraph>
+    <Paragraph Type="Dialogue"><Text>It always is.</Text></Paragraph>
+    <Paragraph Type="Parenthetical"><Text>(beat)</Text></Paragraph>
+  </Content>
+  <TitlePage>
+    <Content>
+      <Paragraph Alignment="Center"><Text>THE KEEPER</Text></Paragraph>
+      <Paragraph Alignment="Center"><Text>Based on a true story</Text></Paragraph>
+      <Paragraph Alignment="Center"><Text>A winter tale</Text></Paragraph>
+      <Paragraph Alignment="Right"><Text>Second draft</Text></Paragraph>
+    </Content>
+  </TitlePage>
+</FinalDraft>
+"#;
+
+#[test]
+fn author_follows_a_credit() {
+    let title_page = parse_fdx(TITLED).title_page.unwrap();
+    assert_eq!(title_page.title, "THE KEEPER");
+    assert_eq!(title_page.credit, None);
+    assert_eq!(title_page.author, None);
+    assert_eq!(title_page.notes, vec!["Based on a true story", "A winter tale"]);
+    assert_eq!(title_page.draft_date.as_deref(), Some("Second draft"));
+    assert_eq!((title_page.span.line, title_page.span.column), (14, 7));
+}
+
+#[test]
+fn paragraphs_are_located_in_the_source() {
+    let script = parse_fdx(TITLED);
+    let scene = &script.scenes[0];
+    assert_eq!((scene.span.line, scene.span.column), (4, 5));
+
+    let lines: Vec<(usize, usize)> = scene.elements.iter().map(|element| (element.span().line, element.span().column)).collect();
+    assert_eq!(lines, vec![(5, 5), (8, 5)]);
+    let ScriptElement::Dialogue { actions, .. } = &scene.elements[0] else {
+        panic!("expected dialogue, got {:?}", scene.elements[0]);
+    };
+    assert_eq!(actions[0].span.line, 6);
+    assert_eq!(&TITLED[actions[0].span.byte_range.clone()], r#"<Paragraph Type="Parenthetical"><Text>(low)</Text></Paragraph>"#);
+}
+
+#[test]
+fn narrator_codes_are_read_as_narration() {
+    let script = FdxParser::new(TITLED.to_string()).with_narrator_codes(["VOICE"]).parse().unwrap();
+    assert!(matches!(
+        &script.scenes[0].elements[1],
+        ScriptElement::Narration { text, voice: Some(voice), .. } if text == "It always is. (beat)" && voice == "VOICE"
+    ));
+    let codes: Vec<&str> = script.characters.iter().map(|character| character.code.as_str()).collect();
+    assert_eq!(codes, vec!["KEEPER"]);
+}
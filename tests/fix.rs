@@ -0,0 +1,118 @@
+use script_parser::{apply_fixes, diff, Diagnostic, Lexer, Parser, Suggestion, TextEdit, Validator};
+
+/// A warning whose fix replaces each range with its replacement.
+fn fix(edits: &[(std::ops::Range<usize>, &str)]) -> Diagnostic {
+    let suggestion = edits.iter().fold(Suggestion::new("Fix it".to_string()), |suggestion, (range, replacement)| {
+        suggestion.with_edit(TextEdit::new(range.clone(), replacement.to_string()))
+    });
+    Diagnostic::warning("test", "Something to fix".to_string()).with_fix(suggestion)
+}
+
+/// Runs the validator over `source` and applies every fix it suggests.
+fn fix_script(source: &str) -> String {
+    let script = Parser::new(Lexer::new(source.to_string()).tokenize()).parse().unwrap();
+    let report = Validator::new().validate(&script);
+    apply_fixes(source, report.diagnostics()).source
+}
+
+#[test]
+fn edits_are_applied_from_the_end() {
+    let outcome = apply_fixes("one two three", &[fix(&[(0..3, "1")]), fix(&[(8..13, "3"), (4..7, "2")])]);
+    assert_eq!(outcome.source, "1 2 3");
+    assert_eq!((outcome.applied, outcome.skipped), (2, 0));
+    assert!(outcome.is_changed());
+}
+
+#[test]
+fn overlapping_fixes_are_skipped_whole() {
+    let outcome = apply_fixes(
+        "one two three",
+        &[
+            fix(&[(4..7, "2")]),
+            // Overlaps the first fix, so its other edit is left out too
+            fix(&[(0..3, "1"), (6..9, "x")]),
+            // Touching is not overlapping
+            fix(&[(7..8, "_")]),
+        ],
+    );
+    assert_eq!(outcome.source, "one 2_three");
+    assert_eq!((outcome.applied, outcome.skipped), (2, 1));
+}
+
+#[test]
+fn invalid_ranges_are_skipped() {
+    let source = "José";
+    #[allow(clippy::reversed_empty_ranges)]
+    let outcome = apply_fixes(
+        source,
+        &[fix(&[(0..10, "")]), fix(&[(3..4, "e")]), fix(&[(3..1, "")]), Diagnostic::warning("test", "No fix".to_string())],
+    );
+    assert_eq!(outcome.source, source);
+    assert_eq!((outcome.applied, outcome.skipped), (0, 3));
+    assert!(!outcome.is_changed());
+}
+
+#[test]
+fn deleting_whole_lines_takes_the_line_break() {
+    let source = "ALICE: Alice\n  age: 30\nBOB: Bob\n";
+    assert_eq!(apply_fixes(source, &[fix(&[(0..22, "")])]).source, "BOB: Bob\n");
+    assert_eq!(apply_fixes(source, &[fix(&[(23..31, "")])]).source, "ALICE: Alice\n  age: 30\n");
+    // Part of a line leaves the line break alone
+    assert_eq!(apply_fixes(source, &[fix(&[(5..12, "")])]).source, "ALICE\n  age: 30\nBOB: Bob\n");
+}
+
+#[test]
+fn renamed_speaker_keeps_its_character() {
+    let source = "\
+# Farewell
+
+## Characters
+
+ALICE: Alice Johnson
+CARL: Carl Jones
+
+## Script
+
+[Hall]
+
+ALCIE: Hi there.
+
+ALICE: Bye.
+";
+    assert_eq!(
+        fix_script(source),
+        "\
+# Farewell
+
+## Characters
+
+ALICE: Alice Johnson
+
+## Script
+
+[Hall]
+
+ALICE: Hi there.
+
+ALICE: Bye.
+"
+    );
+}
+
+#[test]
+fn renamed_only_speaker_keeps_its_character() {
+    let source = "# Farewell\n\n## Characters\n\nALICE: Alice Johnson\n\n## Script\n\n[Hall]\n\nALCIE: Hi there.\n";
+    let fixed = fix_script(source);
+    assert_eq!(fixed, source.replace("ALCIE", "ALICE"));
+    // and nothing is left to fix
+    assert_eq!(fix_script(&fixed), fixed);
+}
+
+#[test]
+fn diff_shows_the_changes() {
+    let diff = diff("ALCIE: Hi.\nBOB: Bye.\n", "ALICE: Hi.\nBOB: Bye.\n", "farewell.md");
+    assert_eq!(
+        diff,
+        "--- a/farewell.md\n+++ b/farewell.md\n@@ -1,2 +1,2 @@\n-ALCIE: Hi.\n+ALICE: Hi.\n BOB: Bye.\n"
+    );
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use common::{parse_markdown, render, without_spans};
+use script_parser::{FountainParser, FountainRenderer, MarkdownRenderer, Parenthetical, Scene, Script, ScriptElement, Span, TitlePage};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fountain")
+}
+
+/// The `.fountain` files of the corpus, which are all written the way
+/// `FountainRenderer` writes them. `markup.fountain` is left out: it uses
+/// syntax the writer never produces.
+fn corpus() -> Vec<(String, String)> {
+    let mut files: Vec<_> = fs::read_dir(fixture_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "fountain"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    files
+        .into_iter()
+        .filter(|path| path.file_stem().is_some_and(|stem| stem != "markup"))
+        .map(|path| (path.display().to_string(), fs::read_to_string(&path).unwrap()))
+        .collect()
+}
+
+fn parse_fountain(source: &str) -> Script {
+    FountainParser::new(source.to_string()).parse().unwrap()
+}
+
+#[test]
+fn fountain_round_trips() {
+    for (name, source) in corpus() {
+        let script = parse_fountain(&source);
+        assert_eq!(render(&FountainRenderer, &script), source, "{}", name);
+    }
+}
+
+#[test]
+fn fountain_round_trips_through_markdown() {
+    for (name, source) in corpus() {
+        let markdown = render(&MarkdownRenderer, &parse_fountain(&source));
+        let script = parse_markdown(&markdown);
+
+        assert_eq!(render(&MarkdownRenderer, &script), markdown, "{}", name);
+        assert_eq!(render(&FountainRenderer, &script), source, "{}", name);
+    }
+}
+
+#[test]
+fn markdown_round_trips_through_fountain() {
+    let source = fs::read_to_string(fixture_dir().join("sample.md")).unwrap();
+    let script = parse_markdown(&source);
+    let imported = parse_fountain(&render(&FountainRenderer, &script));
+
+    // Fountain has no cast list, so only the title page and scenes survive
+    assert_eq!(without_spans(&imported.scenes), without_spans(&script.scenes));
+    assert_eq!(
+        imported.title_page.map(|page| TitlePage { span: Span::default(), ..page }),
+        script.title_page.map(|page| TitlePage { span: Span::default(), ..page })
+    );
+}
+
+#[test]
+fn characters_are_inferred_from_cues() {
+    let source = fs::read_to_string(fixture_dir().join("no_title_page.fountain")).unwrap();
+    let script = parse_fountain(&source);
+
+    let characters: Vec<_> = script
+        .characters
+        .iter()
+        .map(|character| (character.code.as_str(), character.name.as_str(), character.span.line))
+        .collect();
+    assert_eq!(characters, vec![("KEEPER", "Keeper", 9), ("HARBOUR MASTER", "Harbour Master", 12)]);
+    assert!(script.title_page.is_none());
+}
+
+#[test]
+fn markup_is_normalised() {
+    let source = fs::read_to_string(fixture_dir().join("markup.fountain")).unwrap();
+    let script = parse_fountain(&source);
+
+    let title_page = script.title_page.as_ref().unwrap();
+    assert_eq!(title_page.title, "_**BRICK & STEEL**_ _**FULL RETIRED**_");
+    assert_eq!(title_page.author.as_deref(), Some("Stu Maschwitz"));
+    assert_eq!(title_page.notes, vec!["Source: Story by KTM"]);
+
+    // The boneyard scene, section, synopsis, scene number, note, extension,
+    // dual dialogue caret, forcing `@` and page break are all dropped
+    assert_eq!(script.scenes.len(), 1);
+    let scene = &script.scenes[0];
+    assert_eq!(scene.location.as_deref(), Some("EXT. BRICK'S PATIO - DAY"));
+    assert_eq!(scene.span.line, 17);
+
+    let summary: Vec<String> = scene
+        .elements
+        .iter()
+        .map(|element| match element {
+            ScriptElement::Dialogue { speaker, text, .. } => format!("{}: {}", speaker, text),
+            ScriptElement::Narration { text, .. } => text.clone(),
+            other => panic!("unexpected element {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            "A gorgeous day. The sun shines.",
+            "STEEL: Beer's ready!",
+            "BRICK: Are they cold?",
+            "McCLANE: Yippee ki-yay.",
+            "THE END",
+        ]
+    );
+}
+
+#[test]
+fn narration_that_looks_like_markup_round_trips_through_markdown() {
+    let narration = |text: &str| ScriptElement::Narration { text: text.to_string(), voice: None, span: Span::default() };
+    let mut scene = Scene::new(Some("Lab".to_string()), Span::default());
+    for text in [
+        "WARNING: Contents hot.",
+        "[The lights flicker]",
+        "(Nobody moves)",
+        "> The door swings open.",
+        "# 1 on the board.",
+        "## Second floor.",
+        "<!-- script-parser: disable -->",
+        "\\o/ cheers the crowd.",
+        "N: is written on the door.",
+        "She reads the label.\nBOB: do not open.\n[sealed]",
+    ] {
+        scene.push(narration(text));
+    }
+    scene.push(ScriptElement::Dialogue {
+        speaker: "BOB".to_string(),
+        text: "Careful.\nALICE: was here.".to_string(),
+        actions: Vec::new(),
+        span: Span::default(),
+    });
+    let mut script = Script::new();
+    script.scenes.push(scene);
+
+    let markdown = render(&MarkdownRenderer, &script);
+    assert!(markdown.contains("\n\\WARNING: Contents hot.\n"), "{}", markdown);
+    assert!(markdown.contains("\n  \\BOB: do not open.\n"), "{}", markdown);
+    assert!(markdown.contains("\nShe reads the label.\n"), "{}", markdown);
+
+    let parsed = parse_markdown(&markdown);
+    assert_eq!(without_spans(&parsed.scenes), without_spans(&script.scenes));
+    assert_eq!(render(&MarkdownRenderer, &parsed), markdown);
+}
+
+#[test]
+fn offsets_inside_a_character_are_moved_back() {
+    let dialogue = |text: &str, offset: usize| ScriptElement::Dialogue {
+        speaker: "ZOÉ".to_string(),
+        text: text.to_string(),
+        actions: vec![Parenthetical { text: "beat".to_string(), offset, span: Span::default() }],
+        span: Span::default(),
+    };
+    let mut scene = Scene::new(Some("Café".to_string()), Span::default());
+    // `Ç` and `é` are two bytes long, so both offsets fall inside a character
+    scene.push(dialogue("Ça va", 1));
+    scene.push(dialogue("ééé", 3));
+    let mut script = Script::new();
+    script.scenes.push(scene);
+
+    let fountain = render(&FountainRenderer, &script);
+    assert!(fountain.contains("\nZOÉ\n(beat)\nÇa va\n"), "{}", fountain);
+    assert!(fountain.contains("\nZOÉ\né\n(beat)\néé\n"), "{}", fountain);
+}
+
+#[test]
+fn narrator_codes_are_read_as_narration() {
+    let source = fs::read_to_string(fixture_dir().join("no_title_page.fountain")).unwrap();
+    let script = FountainParser::new(source).with_narrator_codes(["KEEPER"]).parse().unwrap();
+
+    let voiced: Vec<&str> = script
+        .scenes
+        .iter()
+        .flat_map(|scene| &scene.elements)
+        .filter_map(|element| match element {
+            ScriptElement::Narration { text, voice: Some(_), .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(voiced, vec!["Another storm.", "(to himself) Always another storm."]);
+    let codes: Vec<&str> = script.characters.iter().map(|character| character.code.as_str()).collect();
+    assert_eq!(codes, vec!["HARBOUR MASTER"]);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{parse_markdown, render};
+use script_parser::HtmlRenderer;
+
+const SOURCE: &str = r#"# Tom & Jerry's "Chase" <Pilot>
+
+## Characters
+
+TOM: Tom <the cat> & "Co"
+JERRY: Jerry
+
+## Script
+
+[Kitchen]
+
+TOM: I'll get you (whispering) <soon> & "forever".
+N: The mouse runs.
+> CUT TO:
+"#;
+
+fn render_html(source: &str) -> String {
+    render(&HtmlRenderer::new(), &parse_markdown(source))
+}
+
+/// The lines of `html` between the opening `<body>` tag and the end of the
+/// document, leaving out the head and its stylesheet.
+fn body(html: &str) -> Vec<&str> {
+    html.lines().skip_while(|line| *line != "<body>").collect()
+}
+
+#[test]
+fn text_is_escaped() {
+    let html = render_html(SOURCE);
+
+    assert!(html.contains("<title>Tom &amp; Jerry&#39;s &quot;Chase&quot; &lt;Pilot&gt;</title>"));
+    assert!(html.contains("<h1>TOM &amp; JERRY&#39;S &quot;CHASE&quot; &lt;PILOT&gt;</h1>"));
+    assert!(html.contains("<dd>Tom &lt;the cat&gt; &amp; &quot;Co&quot;</dd>"));
+    assert!(html.contains("<p class=\"speech\">I&#39;ll get you</p>"));
+    assert!(html.contains("<p class=\"speech\">&lt;soon&gt; &amp; &quot;forever&quot;.</p>"));
+    assert!(!html.contains("<soon>"));
+    assert!(!html.contains("<the cat>"));
+}
+
+#[test]
+fn scenes_are_rendered_as_semantic_markup() {
+    let html = render_html(SOURCE);
+
+    assert_eq!(
+        body(&html),
+        [
+            "<body>",
+            "<article class=\"screenplay\">",
+            "<header class=\"title-page\">",
+            "<h1>TOM &amp; JERRY&#39;S &quot;CHASE&quot; &lt;PILOT&gt;</h1>",
+            "</header>",
+            "<section class=\"characters\">",
+            "<h2>Characters</h2>",
+            "<dl>",
+            "<dt>TOM</dt>",
+            "<dd>Tom &lt;the cat&gt; &amp; &quot;Co&quot;</dd>",
+            "<dt>JERRY</dt>",
+            "<dd>Jerry</dd>",
+            "</dl>",
+            "</section>",
+            "<section class=\"scene\">",
+            "<h2 class=\"scene-heading\">KITCHEN</h2>",
+            "<div class=\"dialogue\">",
+            "<p class=\"character\">TOM</p>",
+            "<p class=\"speech\">I&#39;ll get you</p>",
+            "<p class=\"parenthetical\">(whispering)</p>",
+            "<p class=\"speech\">&lt;soon&gt; &amp; &quot;forever&quot;.</p>",
+            "</div>",
+            "<p class=\"narration\">The mouse runs.</p>",
+            "<p class=\"transition\">CUT TO:</p>",
+            "</section>",
+            "</article>",
+            "</body>",
+            "</html>",
+        ]
+    );
+
+    // Every class used in the body is styled
+    for class in ["scene-heading", "character", "parenthetical", "speech", "narration", "transition"] {
+        let styled = html.contains(&format!(".{} {{", class)) || html.contains(&format!(".{}, ", class));
+        assert!(styled, "no style for .{}", class);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{parse_markdown, render};
+use script_parser::{AstFormat, MarkdownRenderer, ScriptElement};
+use std::fs;
+use std::path::Path;
+
+fn read_fixture(name: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)).unwrap()
+}
+
+#[test]
+fn ast_round_trips() {
+    let script = parse_markdown(&read_fixture("fountain/sample.md"));
+    for format in [AstFormat::Json, AstFormat::Yaml] {
+        let document = format.serialize(&script).unwrap();
+        assert_eq!(format.deserialize(&document).unwrap(), script, "{:?}", format);
+    }
+}
+
+#[test]
+fn elements_are_tagged_with_their_type() {
+    let script = parse_markdown(&read_fixture("fountain/sample.md"));
+    let document: serde_json::Value = serde_json::from_str(&AstFormat::Json.serialize(&script).unwrap()).unwrap();
+
+    let types: Vec<&str> = document["scenes"][0]["elements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|element| element["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types[..3], ["narration", "dialogue", "dialogue"]);
+}
+
+#[test]
+fn characters_are_a_list_of_definitions() {
+    let script = parse_markdown("## Characters\n\nALICE: Alice\nBOB: Bob\nALICE: Alicia\n");
+    let json = AstFormat::Json.serialize(&script).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let names: Vec<&str> = document["characters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|character| character["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Alice", "Bob", "Alicia"]);
+
+    // The repeated definition is found again when the list is read back
+    let read = AstFormat::Json.deserialize(&json).unwrap();
+    assert_eq!(read.characters.len(), 2);
+    assert_eq!(read.characters.duplicates().len(), 1);
+    assert_eq!(read.characters.duplicates()[0].name, "Alicia");
+    assert_eq!(read, script);
+}
+
+#[test]
+fn generated_ast_can_leave_out_spans() {
+    let script = AstFormat::Json.deserialize(&read_fixture("interchange/generated.json")).unwrap();
+
+    assert_eq!(script.title_page.as_ref().unwrap().title, "Generated");
+    assert!(script.characters.contains("BOB"));
+    assert!(matches!(
+        &script.scenes[0].elements[1],
+        ScriptElement::Dialogue { speaker, actions, .. } if speaker == "ALICE" && actions.is_empty()
+    ));
+    assert_eq!(
+        render(&MarkdownRenderer, &script),
+        "# Generated\n\n## Characters\n\nALICE: Alice\nBOB: Bob\n\n## Script\n\n[Kitchen]\n\nMorning.\n\nALICE: Coffee?\n\nBOB: Please.\n"
+    );
+}
+
+#[test]
+fn invalid_documents_are_rejected() {
+    let error = AstFormat::Yaml.deserialize("scenes: 3").unwrap_err();
+    assert_eq!(error.to_string(), "Invalid YAML AST");
+}
+
+#[test]
+fn parenthetical_offsets_are_checked() {
+    let document = |text: &str, offsets: &[usize]| {
+        let actions: Vec<String> =
+            offsets.iter().map(|offset| format!(r#"{{"text": "beat", "offset": {}}}"#, offset)).collect();
+        format!(
+            r#"{{"scenes": [{{"elements": [{{"type": "dialogue", "speaker": "ALICE", "text": "{}", "actions": [{}]}}]}}]}}"#,
+            text,
+            actions.join(", ")
+        )
+    };
+
+    for (offsets, problem) in [
+        (&[1][..], "offset 1 of parenthetical 'beat' in ALICE's dialogue in scene 1 is inside a character"),
+        (&[7][..], "offset 7 of parenthetical 'beat' in ALICE's dialogue in scene 1 is past the end of the text"),
+        (&[3, 0][..], "offset 0 of parenthetical 'beat' in ALICE's dialogue in scene 1 is before the parenthetical preceding it"),
+    ] {
+        let error = AstFormat::Json.deserialize(&document("Ça va", offsets)).unwrap_err();
+        assert_eq!(error.to_string(), format!("Invalid AST: {}", problem));
+    }
+
+    let script = AstFormat::Json.deserialize(&document("Ça va", &[0, 3, 3, 6])).unwrap();
+    assert_eq!(
+        render(&MarkdownRenderer, &script),
+        "## Script\n\nALICE: (beat) Ça (beat) (beat) va (beat)\n"
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::{parse_markdown, render};
+use script_parser::LatexRenderer;
+
+const SOURCE: &str = "\
+# Tom & Jerry
+
+## Characters
+
+TOM: Tom_Cat
+
+## Script
+
+[Kitchen #2]
+
+TOM: I will get you (whispering) for 100% sure.
+  Now.
+
+  Or later.
+N: The mouse runs.
+> CUT TO:
+";
+
+#[test]
+fn scenes_are_rendered_as_screenplay_environments() {
+    let latex = render(&LatexRenderer::new(), &parse_markdown(SOURCE));
+    let body = &latex[latex.find("\\thispagestyle{empty}").unwrap()..];
+
+    assert_eq!(
+        body,
+        "\
+\\thispagestyle{empty}
+
+\\begin{characters}
+\\castmember{TOM}{Tom\\_Cat}
+\\end{characters}
+
+\\begin{sceneheading}
+KITCHEN \\#2
+\\end{sceneheading}
+
+\\begin{dialogue}{TOM}
+I will get you
+\\parenthetical{whispering}
+for 100\\% sure. Now.
+
+\\vspace{\\baselineskip}
+Or later.
+\\end{dialogue}
+
+\\begin{narration}
+The mouse runs.
+\\end{narration}
+
+\\begin{transition}
+CUT TO:
+\\end{transition}
+
+\\end{document}
+"
+    );
+    assert!(latex.contains("\\begin{center}\nTOM \\& JERRY\n\\end{center}"));
+
+    // Every environment used in the body is defined in the preamble
+    for environment in ["characters", "sceneheading", "dialogue", "narration", "transition"] {
+        assert!(latex.contains(&format!("\\newenvironment{{{}}}", environment)), "{} is not defined", environment);
+    }
+    assert!(latex.contains("\\newcommand{\\parenthetical}"));
+}
@@ -0,0 +1,58 @@
+use script_parser::{measure, wrap, LayoutProfile};
+
+#[test]
+fn text_wraps_at_word_boundaries() {
+    assert_eq!(wrap("the quick brown fox jumps", 10), vec!["the quick", "brown fox", "jumps"]);
+    assert_eq!(wrap("  spaced   out  ", 20), vec!["spaced out"]);
+}
+
+#[test]
+fn words_longer_than_a_line_are_split() {
+    assert_eq!(wrap("supercalifragilistic", 8), vec!["supercal", "ifragili", "stic"]);
+    assert_eq!(wrap("a abcdefghij b", 4), vec!["a", "abcd", "efgh", "ij b"]);
+}
+
+#[test]
+fn width_is_measured_in_characters() {
+    assert_eq!(measure("café"), 4);
+    assert_eq!(wrap("café café", 4), vec!["café", "café"]);
+    assert_eq!(wrap("ééééé", 2), vec!["éé", "éé", "é"]);
+}
+
+#[test]
+fn empty_text_is_one_empty_line() {
+    assert_eq!(wrap("", 10), vec![""]);
+    assert_eq!(wrap("word", 0), vec!["w", "o", "r", "d"]);
+}
+
+#[test]
+fn profiles_are_looked_up_by_name() {
+    assert_eq!(LayoutProfile::by_name("spec"), Some(LayoutProfile::spec_screenplay()));
+    assert_eq!(LayoutProfile::by_name("a4"), Some(LayoutProfile::a4()));
+    assert_eq!(LayoutProfile::by_name("letter"), None);
+    assert_eq!(LayoutProfile::default(), LayoutProfile::spec_screenplay());
+}
+
+#[test]
+fn spec_profile_follows_the_screenplay_format() {
+    let spec = LayoutProfile::spec_screenplay();
+    // US Letter, with a 1.5" left margin and cues at 3.7"
+    assert!((spec.page_width - 215.9).abs() < 0.01);
+    assert!((spec.page_height - 279.4).abs() < 0.01);
+    assert!((spec.left_margin - 38.1).abs() < 0.01);
+    assert!((spec.character_indent - 93.98).abs() < 0.01);
+    assert_eq!(spec.lines_per_page, 55);
+    // 12pt Courier sets ten characters to the inch
+    assert!((spec.text_width("ten chars.") - 25.4).abs() < 0.01);
+}
+
+#[test]
+fn a4_profile_keeps_the_spec_columns() {
+    let spec = LayoutProfile::spec_screenplay();
+    let a4 = LayoutProfile::a4();
+    assert_eq!((a4.page_width, a4.page_height), (210.0, 297.0));
+    assert!(a4.lines_per_page > spec.lines_per_page);
+    assert_eq!(a4.left_margin, spec.left_margin);
+    assert_eq!(a4.character_indent, spec.character_indent);
+    assert_eq!(a4.columns, spec.columns);
+}
@@ -0,0 +1,330 @@
+use script_parser::{Character, Lexer, Validator, Parser, Scene, Script, ScriptElement, Span, TitlePage, Token};
+
+fn parse(source: &str) -> Script {
+    let tokens = Lexer::new(source.to_string()).tokenize();
+    Parser::new(tokens).parse().unwrap()
+}
+
+/// The elements of the only scene of a script whose `## Script` section is
+/// `body`.
+fn elements(body: &str) -> Vec<ScriptElement> {
+    let mut scenes = parse(&format!("## Script\n\n{}", body)).scenes;
+    assert_eq!(scenes.len(), 1);
+    let Scene { elements, .. } = scenes.remove(0);
+    elements
+}
+
+/// A dialogue element as its speaker, text and parentheticals with their
+/// offsets.
+fn speech(element: &ScriptElement) -> (&str, &str, Vec<(&str, usize)>) {
+    match element {
+        ScriptElement::Dialogue { speaker, text, actions, .. } => (
+            speaker.as_str(),
+            text.as_str(),
+            actions.iter().map(|action| (action.text.as_str(), action.offset)).collect(),
+        ),
+        other => panic!("expected dialogue, got {:?}", other),
+    }
+}
+
+/// The span of the first occurrence of `text` in `source`.
+fn span_of(source: &str, text: &str) -> Span {
+    let start = source.find(text).unwrap();
+    let before = &source[..start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Span::new(
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+        start..start + text.len(),
+    )
+}
+
+const SPANS: &str = "\
+# The Farewell
+
+## Characters
+
+JOSÉ: José Ruiz
+  age: 40s
+
+## Script
+
+[Kitchen]
+
+JOSÉ: ¡Hola! (beat) Más café.
+(pouring)
+
+The kettle whistles.
+";
+
+#[test]
+fn spans_point_at_the_source() {
+    let script = parse(SPANS);
+
+    let title_page = script.title_page.as_ref().unwrap();
+    assert_eq!(title_page.span, span_of(SPANS, "# The Farewell"));
+
+    let character = script.characters.get("JOSÉ").unwrap();
+    let definition = span_of(SPANS, "JOSÉ: José Ruiz");
+    assert_eq!(character.span, definition.to(&span_of(SPANS, "age: 40s")));
+
+    let scene = &script.scenes[0];
+    assert_eq!(scene.span, span_of(SPANS, "[Kitchen]").to(&span_of(SPANS, "The kettle whistles.")));
+
+    let ScriptElement::Dialogue { span, actions, .. } = &scene.elements[0] else {
+        panic!("expected dialogue, got {:?}", scene.elements[0]);
+    };
+    assert_eq!(*span, span_of(SPANS, "JOSÉ: ¡Hola!").to(&span_of(SPANS, "(pouring)")));
+    assert_eq!(actions[0].span, span_of(SPANS, "(beat)"));
+    assert_eq!(actions[1].span, span_of(SPANS, "(pouring)"));
+
+    assert_eq!(*scene.elements[1].span(), span_of(SPANS, "The kettle whistles."));
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    let script = parse(SPANS);
+    let ScriptElement::Dialogue { actions, .. } = &script.scenes[0].elements[0] else {
+        panic!("expected dialogue");
+    };
+    // `JOSÉ: ¡Hola! ` is 13 characters but 15 bytes
+    assert_eq!((actions[0].span.line, actions[0].span.column), (12, 14));
+}
+
+#[test]
+fn lexer_tokens_carry_spans() {
+    let tokens = Lexer::new(SPANS.to_string()).tokenize();
+    let spans: Vec<Span> = tokens.iter().filter_map(|token| token.span().cloned()).collect();
+    let lines: Vec<usize> = spans.iter().map(|span| span.line).collect();
+
+    assert_eq!(lines, vec![1, 3, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15]);
+    for span in spans.iter().filter(|span| !span.byte_range.is_empty()) {
+        assert_eq!(*span, span_of(SPANS, &SPANS[span.byte_range.clone()]), "{:?}", span);
+    }
+}
+
+#[test]
+fn title_section_becomes_a_title_page() {
+    let script = parse(
+        "# The Long Goodbye\n\
+         \n\
+         credit: Written by\n\
+         Author: Jane Doe\n\
+         Draft date: 1 May 2026\n\
+         Contact: Jane Doe\n\
+         \x20 12 Main Street\n\
+         Copyright: (c) 2026 Jane Doe\n\
+         A story about leaving.\n\
+         \n\
+         ## Characters\n",
+    );
+
+    let title_page = script.title_page.unwrap();
+    assert_eq!(
+        TitlePage { span: Span::default(), ..title_page },
+        TitlePage {
+            title: "The Long Goodbye".to_string(),
+            credit: Some("Written by".to_string()),
+            author: Some("Jane Doe".to_string()),
+            draft_date: Some("1 May 2026".to_string()),
+            contact: Some("Jane Doe\n12 Main Street".to_string()),
+            copyright: Some("(c) 2026 Jane Doe".to_string()),
+            notes: vec!["A story about leaving.".to_string()],
+            span: Span::default(),
+        }
+    );
+    assert_eq!((title_page.span.line, title_page.span.byte_range.start), (1, 0));
+}
+
+#[test]
+fn title_field_overrides_the_heading() {
+    let script = parse("# Working Title\nTitle: The Long Goodbye\nAuthors: Jane Doe\nAuthors: John Roe\n");
+    let title_page = script.title_page.unwrap();
+    assert_eq!(title_page.title, "The Long Goodbye");
+    assert_eq!(title_page.author.as_deref(), Some("Jane Doe\nJohn Roe"));
+}
+
+#[test]
+fn bare_heading_is_an_empty_title() {
+    for heading in ["#", "# ", "#   "] {
+        let script = parse(&format!("{}\nAuthor: Jane Doe\n", heading));
+        let title_page = script.title_page.unwrap();
+        assert_eq!(title_page.title, "", "{:?}", heading);
+        assert_eq!(title_page.author.as_deref(), Some("Jane Doe"));
+    }
+}
+
+#[test]
+fn inline_parentheticals_are_taken_out_of_the_text() {
+    let elements = elements("ALICE: (whispering) Ça va. (beat) But I'm staying. (sighs)\n");
+    assert_eq!(
+        speech(&elements[0]),
+        ("ALICE", "Ça va. But I'm staying.", vec![("whispering", 0), ("beat", 8), ("sighs", 24)])
+    );
+}
+
+#[test]
+fn parenthetical_below_dialogue_attaches_to_it() {
+    let elements = elements("BOB: Then I'll go alone.\n(turning away)\n\n(Alice sits down)\n");
+    assert_eq!(speech(&elements[0]), ("BOB", "Then I'll go alone.", vec![("turning away", 19)]));
+    // After a blank line it is a stage direction of its own
+    assert!(matches!(&elements[1], ScriptElement::Action { text, .. } if text == "Alice sits down"));
+    assert_eq!(elements.len(), 2);
+}
+
+#[test]
+fn unclosed_parenthesis_is_kept_as_text() {
+    let elements = elements("ALICE: I said (and I meant it\n");
+    assert_eq!(speech(&elements[0]), ("ALICE", "I said (and I meant it", vec![]));
+}
+
+#[test]
+fn indented_lines_continue_the_speech() {
+    let elements = elements(
+        "ALICE: I've been thinking\n\
+         \x20 about this for a long time.\n\
+         \n\
+         \x20 And another thing: I'm taking the car.\n",
+    );
+    assert_eq!(
+        speech(&elements[0]),
+        ("ALICE", "I've been thinking about this for a long time.\nAnd another thing: I'm taking the car.", vec![])
+    );
+    assert_eq!(elements.len(), 1);
+}
+
+#[test]
+fn indented_lines_continue_narration() {
+    let elements = elements("N: The silence stretches.\n  Nobody moves.\n\n  Then the phone rings.\n");
+    assert!(matches!(
+        &elements[0],
+        ScriptElement::Narration { text, .. } if text == "The silence stretches. Nobody moves.\nThen the phone rings."
+    ));
+    assert_eq!(elements.len(), 1);
+}
+
+#[test]
+fn parentheticals_on_continuation_lines_keep_their_place() {
+    // Offsets are in bytes: `Más café.` is 11 bytes, plus the joining space
+    let elements = elements("JOSÉ: Más café.\n  (pouring) Ahora sí. (beat)\n\n  ¿Y tú?\n");
+    assert_eq!(
+        speech(&elements[0]),
+        ("JOSÉ", "Más café. Ahora sí.\n¿Y tú?", vec![("pouring", 12), ("beat", 22)])
+    );
+}
+
+#[test]
+fn indented_script_lines_are_not_continuations() {
+    let source = "## Script\n\n[Hall]\n\nALICE: Hi.\n  BOB: Hello.\n  [Kitchen]\n  > CUT TO:\n";
+    let scenes = parse(source).scenes;
+
+    assert_eq!(scenes.len(), 2);
+    assert_eq!(speech(&scenes[0].elements[0]), ("ALICE", "Hi.", vec![]));
+    assert_eq!(speech(&scenes[0].elements[1]), ("BOB", "Hello.", vec![]));
+    assert_eq!(scenes[1].location.as_deref(), Some("Kitchen"));
+    assert!(matches!(&scenes[1].elements[0], ScriptElement::Transition { text, .. } if text == "CUT TO:"));
+}
+
+const CHARACTERS: &str = "\
+## Characters
+
+ZOE: Zoe Park
+  description: The landlady
+  age: 60s
+  aliases: LANDLADY, MRS PARK
+ALICE: Alice Johnson
+  BOB: Bob Smith
+  mood: restless
+";
+
+#[test]
+fn characters_keep_declaration_order_and_metadata() {
+    let script = parse(CHARACTERS);
+    let codes: Vec<&str> = script.characters.iter().map(|character| character.code.as_str()).collect();
+    assert_eq!(codes, vec!["ZOE", "ALICE", "BOB", "mood"]);
+
+    let zoe = script.characters.get("ZOE").unwrap();
+    assert_eq!(
+        Character { span: Span::default(), ..zoe.clone() },
+        Character {
+            code: "ZOE".to_string(),
+            name: "Zoe Park".to_string(),
+            description: Some("The landlady".to_string()),
+            age: Some("60s".to_string()),
+            aliases: vec!["LANDLADY".to_string(), "MRS PARK".to_string()],
+            span: Span::default(),
+        }
+    );
+    assert_eq!(script.characters.resolve("MRS PARK").map(|character| character.code.as_str()), Some("ZOE"));
+}
+
+#[test]
+fn indented_definitions_are_not_dropped() {
+    let tokens = Lexer::new(CHARACTERS.to_string()).tokenize();
+    let lexed: Vec<String> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::CharacterDef { code, name, .. } => Some(format!("{}: {}", code, name)),
+            Token::CharacterAttr { key, value, .. } => Some(format!("  {}: {}", key, value)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        lexed,
+        vec![
+            "ZOE: Zoe Park",
+            "  description: The landlady",
+            "  age: 60s",
+            "  aliases: LANDLADY, MRS PARK",
+            "ALICE: Alice Johnson",
+            "BOB: Bob Smith",
+            "mood: restless",
+        ]
+    );
+    // so that an unknown attribute is reported rather than lost
+    let report = Validator::new().validate(&parse(CHARACTERS));
+    let invalid: Vec<Option<usize>> = report
+        .diagnostics()
+        .iter()
+        .filter(|diagnostic| diagnostic.code == "invalid-character-code")
+        .map(|diagnostic| diagnostic.span.as_ref().map(|span| span.line))
+        .collect();
+    assert_eq!(invalid, vec![Some(9)]);
+}
+
+#[test]
+fn scenes_with_only_a_heading_are_kept() {
+    let scenes = parse("## Script\n\nALICE: Hi.\n\n[Hallway]\n\n[Living Room]\n\n[Attic]\n").scenes;
+    let locations: Vec<Option<&str>> = scenes.iter().map(|scene| scene.location.as_deref()).collect();
+    assert_eq!(locations, vec![None, Some("Hallway"), Some("Living Room"), Some("Attic")]);
+    assert!(scenes[1..].iter().all(|scene| scene.elements.is_empty()));
+
+    // A script without dialogue before its first heading has no untitled scene
+    assert_eq!(parse("## Script\n\n[Hallway]\n").scenes.len(), 1);
+}
+
+#[test]
+fn narrator_lines_are_narration() {
+    let elements = elements("N: The house is quiet. (beat) Too quiet.\n  Somewhere, a clock.\n\nVOICE: Hello?\n");
+    assert!(matches!(
+        &elements[0],
+        ScriptElement::Narration { text, voice: Some(voice), .. }
+            if text == "The house is quiet. (beat) Too quiet. Somewhere, a clock." && voice == "N"
+    ));
+    assert_eq!(speech(&elements[1]), ("VOICE", "Hello?", vec![]));
+}
+
+#[test]
+fn narrator_codes_can_be_added() {
+    let tokens = Lexer::new("## Script\n\nVOICE: Hello? (beat)\nN: Nobody answers.\n".to_string()).tokenize();
+    let script = Parser::new(tokens).with_narrator_codes(["VOICE"]).parse().unwrap();
+    let narration: Vec<(&str, &str)> = script.scenes[0]
+        .elements
+        .iter()
+        .map(|element| match element {
+            ScriptElement::Narration { text, voice: Some(voice), .. } => (voice.as_str(), text.as_str()),
+            other => panic!("expected narration, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(narration, vec![("VOICE", "Hello? (beat)"), ("N", "Nobody answers.")]);
+}
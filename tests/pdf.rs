@@ -0,0 +1,67 @@
+use script_parser::{LayoutProfile, PdfRenderer, RenderOptions, Renderer, Scene, Script, ScriptElement, Span, TitlePage};
+
+/// Number of pages in a rendered PDF, counted from its page objects.
+fn page_count(pdf: &[u8]) -> usize {
+    let pdf = String::from_utf8_lossy(pdf);
+    pdf.matches("/Type/Page").count() - pdf.matches("/Type/Pages").count()
+}
+
+fn render_pdf(script: &Script, options: &RenderOptions) -> Vec<u8> {
+    let mut out = Vec::new();
+    PdfRenderer.render(script, &mut out, options).unwrap();
+    out
+}
+
+/// A script of `scenes` scenes, each with a heading and `lines` one-line
+/// narration elements.
+fn script(scenes: usize, lines: usize) -> Script {
+    let mut script = Script::new();
+    for number in 1..=scenes {
+        let mut scene = Scene::new(Some(format!("Room {}", number)), Span::default());
+        for _ in 0..lines {
+            scene.push(ScriptElement::Narration {
+                text: "The clock ticks.".to_string(),
+                voice: None,
+                span: Span::default(),
+            });
+        }
+        script.scenes.push(scene);
+    }
+    script
+}
+
+#[test]
+fn short_script_fits_on_one_page() {
+    assert_eq!(page_count(&render_pdf(&script(1, 10), &RenderOptions::new())), 1);
+}
+
+#[test]
+fn long_scene_continues_on_new_pages() {
+    // The heading takes two lines and each narration two more, so 27 fit on
+    // the 55 lines of the first page and 28 on each page after it
+    assert_eq!(page_count(&render_pdf(&script(1, 27), &RenderOptions::new())), 1);
+    assert_eq!(page_count(&render_pdf(&script(1, 28), &RenderOptions::new())), 2);
+    assert_eq!(page_count(&render_pdf(&script(1, 27 + 28 * 3), &RenderOptions::new())), 4);
+}
+
+#[test]
+fn later_scenes_are_not_drawn_past_the_last_page() {
+    // 10 scenes of 42 lines each need 420 lines, or 8 pages of 55
+    assert_eq!(page_count(&render_pdf(&script(10, 20), &RenderOptions::new())), 8);
+}
+
+#[test]
+fn title_page_is_a_page_of_its_own() {
+    let mut titled = script(1, 10);
+    titled.title_page = Some(TitlePage::new("The Long Goodbye".to_string(), Span::default()));
+    assert_eq!(page_count(&render_pdf(&titled, &RenderOptions::new())), 2);
+}
+
+#[test]
+fn a4_pages_hold_more_lines() {
+    // 27 + 28 narrations fill two spec pages, while A4 fits 29 + 30
+    let script = script(1, 59);
+    let a4 = RenderOptions::new().with_profile(LayoutProfile::a4());
+    assert_eq!(page_count(&render_pdf(&script, &RenderOptions::new())), 3);
+    assert_eq!(page_count(&render_pdf(&script, &a4)), 2);
+}
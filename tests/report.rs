@@ -0,0 +1,98 @@
+use script_parser::{to_json, to_sarif, Diagnostic, Lexer, Parser, Span, ValidationReport, Validator};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+fn validate_fixture(name: &str) -> ValidationReport {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/validation")
+        .join(name);
+    let tokens = Lexer::new(fs::read_to_string(&path).unwrap()).tokenize();
+    Validator::new().validate(&Parser::new(tokens).parse().unwrap())
+}
+
+fn sarif(report: &ValidationReport, file_name: &str) -> Value {
+    serde_json::from_str(&to_sarif(report, file_name).unwrap()).unwrap()
+}
+
+#[test]
+fn json_report_lists_every_diagnostic() {
+    let report = validate_fixture("characters.md");
+    let document: Value = serde_json::from_str(&to_json(&report, "characters.md").unwrap()).unwrap();
+
+    assert_eq!(document["file"], "characters.md");
+    assert_eq!((document["errors"].as_u64(), document["warnings"].as_u64()), (Some(3), Some(1)));
+    let diagnostics = document["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 4);
+
+    let duplicate = diagnostics.iter().find(|diagnostic| diagnostic["code"] == "duplicate-character").unwrap();
+    assert_eq!(duplicate["severity"], "error");
+    assert_eq!(duplicate["span"]["line"], 8);
+    assert_eq!(duplicate["related"][0]["span"]["line"], 6);
+    assert_eq!(duplicate["suggestion"]["message"], "Remove the repeated definition");
+}
+
+#[test]
+fn sarif_log_has_the_expected_shape() {
+    let log = sarif(&validate_fixture("characters.md"), "scripts/characters.md");
+
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["$schema"], "https://json.schemastore.org/sarif-2.1.0.json");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "script-parser");
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rules, vec!["duplicate-character", "undefined-speaker", "unused-character"]);
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    let duplicate = results.iter().find(|result| result["ruleId"] == "duplicate-character").unwrap();
+    assert_eq!(duplicate["level"], "error");
+    assert_eq!(
+        duplicate["locations"][0]["physicalLocation"],
+        json!({
+            "artifactLocation": { "uri": "scripts/characters.md" },
+            "region": { "startLine": 8, "startColumn": 1 },
+        })
+    );
+    assert_eq!(duplicate["relatedLocations"][0]["physicalLocation"]["region"]["startLine"], 6);
+    assert_eq!(duplicate["relatedLocations"][0]["message"]["text"], "'BOB' first defined here");
+
+    // The repeated definition can be deleted, so the result carries a fix
+    let changes = &duplicate["fixes"][0]["artifactChanges"][0];
+    assert_eq!(changes["artifactLocation"]["uri"], "scripts/characters.md");
+    assert!(changes["replacements"][0]["deletedRegion"]["byteLength"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn sarif_omits_regions_without_a_line() {
+    let mut report = ValidationReport::new();
+    report.push(Diagnostic::warning("empty-scene", "Scene has no content".to_string()).with_span(&Span::default()));
+
+    let location = &sarif(&report, "farewell.json")["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+    assert_eq!(*location, json!({ "artifactLocation": { "uri": "farewell.json" } }));
+}
+
+#[test]
+fn sarif_uris_are_encoded() {
+    let mut report = ValidationReport::new();
+    report.push(Diagnostic::warning("empty-scene", "Scene has no content".to_string()).with_span(&Span::new(3, 1, 0..1)));
+    let uri = |file_name: &str| {
+        sarif(&report, file_name)["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(uri("scripts/The Farewell #2.md"), "scripts/The%20Farewell%20%232.md");
+    assert_eq!(uri("José.md"), "Jos%C3%A9.md");
+    if cfg!(windows) {
+        assert_eq!(uri(r"C:\scripts\a b.md"), "file:///C:/scripts/a%20b.md");
+    } else {
+        assert_eq!(uri("/home/jane/a b.md"), "file:///home/jane/a%20b.md");
+    }
+}
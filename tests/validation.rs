@@ -0,0 +1,446 @@
+use script_parser::{
+    Character, CodeGrammar, SuppressionScope, RuleLevel, CONFIG_FILE_NAME, Diagnostic, Span, Lexer, Parser, Rule, RuleRegistry, Scene, Script, ScriptElement, Severity,
+    ValidationReport, Validator, ValidatorConfig,
+};
+use std::fs;
+use std::path::Path;
+
+use Severity::{Error, Warning};
+
+fn parse_fixture(name: &str) -> Script {
+    parse_fixture_with_grammar(name, CodeGrammar::default())
+}
+
+fn parse_fixture_with_grammar(name: &str, grammar: CodeGrammar) -> Script {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/validation")
+        .join(name);
+    let source = fs::read_to_string(&path).unwrap();
+    let tokens = Lexer::new(source).with_code_grammar(grammar).tokenize();
+    Parser::new(tokens).parse().unwrap()
+}
+
+/// The findings for a fixture as (severity, rule, line), sorted so tests do
+/// not depend on the order rules run in.
+fn findings(report: &ValidationReport) -> Vec<(Severity, &'static str, Option<usize>)> {
+    let mut findings: Vec<_> = report
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.code, diagnostic.span.as_ref().map(|span| span.line)))
+        .collect();
+    findings.sort();
+    findings
+}
+
+fn parse_source(source: &str) -> Script {
+    Parser::new(Lexer::new(source.to_string()).tokenize()).parse().unwrap()
+}
+
+fn validate_fixture(name: &str) -> Vec<(Severity, &'static str, Option<usize>)> {
+    findings(&Validator::new().validate(&parse_fixture(name)))
+}
+
+#[test]
+fn clean_script_has_no_findings() {
+    assert_eq!(validate_fixture("clean.md"), vec![]);
+}
+
+#[test]
+fn reserved_narrator_code_is_reported_once() {
+    assert_eq!(
+        validate_fixture("reserved_narrator.md"),
+        vec![(Warning, "reserved-code", Some(6))]
+    );
+}
+
+#[test]
+fn narrator_codes_are_reserved_too() {
+    let validator = Validator::new().with_narrator_codes(["ALICE"]);
+    assert_eq!(
+        findings(&validator.validate(&parse_fixture("reserved_narrator.md"))),
+        vec![(Warning, "reserved-code", Some(5)), (Warning, "reserved-code", Some(6))]
+    );
+    // The rule keeps its place among the built-in rules
+    let ids: Vec<&str> = validator.registry().iter().map(|rule| rule.id()).collect();
+    assert_eq!(ids.iter().filter(|id| **id == "reserved-code").count(), 1);
+    assert_eq!(ids[4], "reserved-code");
+}
+
+#[test]
+fn narrator_codes_are_not_undefined_speakers() {
+    // A script parsed without the narrator codes keeps their lines as dialogue
+    let script = parse_source("# Play\n\n## Characters\n\nALICE: Alice\n\n## Script\n\nALICE: Hi.\nVOICE: Who's there?\n");
+    let undefined = |validator: Validator| -> Vec<Option<usize>> {
+        findings(&validator.validate(&script))
+            .into_iter()
+            .filter(|(_, code, _)| *code == "undefined-speaker")
+            .map(|(_, _, line)| line)
+            .collect()
+    };
+
+    assert_eq!(undefined(Validator::new()), vec![Some(10)]);
+    assert_eq!(undefined(Validator::new().with_narrator_codes(["VOICE"])), vec![]);
+}
+
+#[test]
+fn empty_title_is_reported() {
+    assert_eq!(
+        validate_fixture("empty_title.md"),
+        vec![(Error, "empty-title", Some(1))]
+    );
+}
+
+#[test]
+fn empty_scene_is_reported_once() {
+    assert_eq!(
+        validate_fixture("empty_scene.md"),
+        vec![(Warning, "empty-scene", Some(9))]
+    );
+}
+
+#[test]
+fn character_problems() {
+    assert_eq!(
+        validate_fixture("characters.md"),
+        vec![
+            (Warning, "unused-character", Some(7)),
+            (Error, "duplicate-character", Some(8)),
+            (Error, "undefined-speaker", Some(14)),
+            (Error, "undefined-speaker", Some(18)),
+        ]
+    );
+}
+
+#[test]
+fn duplicates_are_kept_by_the_parser() {
+    let script = parse_fixture("characters.md");
+    let codes: Vec<&str> = script.characters.iter().map(|character| character.code.as_str()).collect();
+    assert_eq!(codes, vec!["ALICE", "BOB", "CARL"]);
+
+    let duplicates = script.characters.duplicates();
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!((duplicates[0].code.as_str(), duplicates[0].span.line), ("BOB", 8));
+    assert_eq!(script.characters.get("BOB").unwrap().span.line, 6);
+}
+
+#[test]
+fn duplicate_definition_points_at_both_locations() {
+    let report = Validator::new().validate(&parse_fixture("characters.md"));
+    let duplicate = report.errors().find(|diagnostic| diagnostic.code == "duplicate-character").unwrap();
+
+    assert_eq!(duplicate.message, "Duplicate character code 'BOB'");
+    assert_eq!(duplicate.span.as_ref().map(|span| span.line), Some(8));
+    let related: Vec<(usize, &str)> =
+        duplicate.related.iter().map(|related| (related.span.line, related.message.as_str())).collect();
+    assert_eq!(related, vec![(6, "'BOB' first defined here")]);
+    // The same name twice can simply be removed
+    assert!(duplicate.suggestion.as_ref().unwrap().is_fixable());
+}
+
+#[test]
+fn conflicting_names_are_reported() {
+    let script = parse_source(
+        "# Names\n\n## Characters\n\nALICE: Alice Johnson\nALICE: Alice Cooper\n\n## Script\n\n[Hall]\n\nALICE: Hi.\n",
+    );
+    let report = Validator::new().validate(&script);
+    let duplicates: Vec<&Diagnostic> =
+        report.diagnostics().iter().filter(|diagnostic| diagnostic.code == "duplicate-character").collect();
+
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(
+        duplicates[0].message,
+        "Conflicting definitions for character code 'ALICE': 'Alice Johnson' and 'Alice Cooper'"
+    );
+    assert_eq!(duplicates[0].span.as_ref().map(|span| span.line), Some(6));
+    assert_eq!(duplicates[0].related[0].span.line, 5);
+    // Which name is right is up to the writer
+    assert!(!duplicates[0].suggestion.as_ref().unwrap().is_fixable());
+}
+
+#[test]
+fn mistyped_speakers_get_a_fix() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/validation/characters.md");
+    let source = fs::read_to_string(path).unwrap();
+    let report = Validator::new().validate(&parse_fixture("characters.md"));
+    let suggestions: Vec<(&str, bool)> = report
+        .diagnostics()
+        .iter()
+        .filter(|diagnostic| diagnostic.code == "undefined-speaker")
+        .map(|diagnostic| {
+            let suggestion = diagnostic.suggestion.as_ref().unwrap();
+            (suggestion.message.as_str(), suggestion.is_fixable())
+        })
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec![
+            ("Did you mean 'ALICE' (Alice Johnson)?", true),
+            ("Add 'ZED: Character Name' to the character definitions", false),
+        ]
+    );
+
+    let typo = report.errors().find(|diagnostic| diagnostic.code == "undefined-speaker").unwrap();
+    let edit = &typo.suggestion.as_ref().unwrap().edits[0];
+    assert_eq!(&source[edit.byte_range.clone()], "ALCIE");
+    assert_eq!(edit.replacement, "ALICE");
+}
+
+#[test]
+fn missing_sections() {
+    assert_eq!(
+        validate_fixture("missing_sections.md"),
+        vec![
+            (Error, "missing-characters", None),
+            (Error, "missing-script", None),
+        ]
+    );
+}
+
+#[test]
+fn suppression_comments_silence_rules() {
+    assert_eq!(
+        validate_fixture("suppressed.md"),
+        vec![(Error, "undefined-speaker", Some(19))]
+    );
+}
+
+#[test]
+fn suppression_covers_only_the_next_line() {
+    let script = parse_fixture("suppressed.md");
+    let suppressions: Vec<(SuppressionScope, usize, Vec<&str>)> = script
+        .suppressions
+        .iter()
+        .map(|suppression| (suppression.scope, suppression.span.line, suppression.rules.iter().map(String::as_str).collect()))
+        .collect();
+    assert_eq!(
+        suppressions,
+        vec![
+            (SuppressionScope::File, 3, vec!["unused-character"]),
+            (SuppressionScope::NextLine, 14, vec!["undefined-speaker"]),
+        ]
+    );
+
+    let at = |line| Some(Span::new(line, 1, 0..0));
+    assert!(script.is_suppressed("undefined-speaker", at(15).as_ref()));
+    assert!(!script.is_suppressed("undefined-speaker", at(16).as_ref()));
+    assert!(!script.is_suppressed("empty-scene", at(15).as_ref()));
+    assert!(script.is_suppressed("unused-character", None));
+}
+
+#[test]
+fn config_sets_rule_levels() {
+    let config = ValidatorConfig::from_toml(
+        "[rules]\nunused-character = \"off\"\nundefined-speaker = \"warning\"\nduplicate-character = \"error\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.level("unused-character"), Some(RuleLevel::Off));
+    assert_eq!(config.level("empty-scene"), None);
+
+    let report = Validator::from_config(config).validate(&parse_fixture("characters.md"));
+    assert_eq!(
+        findings(&report),
+        vec![
+            (Warning, "undefined-speaker", Some(14)),
+            (Warning, "undefined-speaker", Some(18)),
+            (Error, "duplicate-character", Some(8)),
+        ]
+    );
+}
+
+#[test]
+fn config_errors_are_reported() {
+    let unknown = ValidatorConfig::from_toml("[rules]\nunused-characters = \"off\"\n").unwrap();
+    let error = unknown.check_rules(&RuleRegistry::builtin()).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown rule(s) in configuration: unused-characters");
+
+    assert!(ValidatorConfig::from_toml("[rules]\nunused-character = \"loud\"\n").is_err());
+    assert!(ValidatorConfig::from_toml("[character-codes]\nemoji = true\n").is_err());
+    assert!(ValidatorConfig::from_toml("[linting]\n").is_err());
+}
+
+#[test]
+fn config_file_is_found_in_parent_directories() {
+    let root = std::env::temp_dir().join(format!("script-parser-config-{}", std::process::id()));
+    let nested = root.join("act-one/scenes");
+    fs::create_dir_all(&nested).unwrap();
+    assert_eq!(ValidatorConfig::discover(&nested), None);
+
+    fs::write(root.join(CONFIG_FILE_NAME), "[character-codes]\ndigits = false\n").unwrap();
+    let found = ValidatorConfig::discover(&nested);
+    assert_eq!(found, Some(root.join(CONFIG_FILE_NAME)));
+    let config = ValidatorConfig::load(&found.unwrap()).unwrap();
+    assert!(!config.character_codes.digits);
+    assert!(config.character_codes.underscores);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn lenient_character_codes_are_accepted() {
+    let script = parse_fixture("character_codes.md");
+    let speakers: Vec<&str> = script.scenes[0]
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            ScriptElement::Dialogue { speaker, .. } => Some(speaker.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(speakers, vec!["COP2", "DR_LEE", "JOSÉ", "MRS. SMITH"]);
+    assert_eq!(validate_fixture("character_codes.md"), vec![]);
+}
+
+#[test]
+fn strict_character_codes_are_reported() {
+    let config = ValidatorConfig {
+        character_codes: CodeGrammar::strict(),
+        ..ValidatorConfig::default()
+    };
+    let script = parse_fixture_with_grammar("character_codes.md", config.character_codes);
+    let report = Validator::from_config(config).validate(&script);
+
+    assert_eq!(
+        findings(&report),
+        vec![
+            (Warning, "unused-character", Some(5)),
+            (Warning, "unused-character", Some(6)),
+            (Warning, "unused-character", Some(7)),
+            (Warning, "unused-character", Some(8)),
+            (Error, "invalid-character-code", Some(5)),
+            (Error, "invalid-character-code", Some(6)),
+            (Error, "invalid-character-code", Some(7)),
+            (Error, "invalid-character-code", Some(8)),
+        ]
+    );
+}
+
+struct Repeating;
+
+impl Rule for Repeating {
+    fn id(&self) -> &'static str {
+        "repeating"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reports every scene twice"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, script: &Script, diagnostics: &mut Vec<Diagnostic>) {
+        for scene in script.scenes.iter().chain(&script.scenes) {
+            diagnostics.push(self.diagnostic("Scene has no content".to_string()).with_span(&scene.span));
+        }
+    }
+}
+
+#[test]
+fn overlapping_rules_report_each_issue_once() {
+    let mut registry = RuleRegistry::builtin();
+    registry.register(Box::new(Repeating));
+    let validator = Validator::new().with_registry(registry);
+
+    let report = validator.validate(&parse_fixture("empty_scene.md"));
+    assert_eq!(
+        findings(&report),
+        vec![
+            (Warning, "empty-scene", Some(9)),
+            (Warning, "repeating", Some(9)),
+            (Warning, "repeating", Some(11)),
+        ]
+    );
+}
+
+#[test]
+fn report_ignores_repeated_findings() {
+    let mut report = ValidationReport::new();
+    let diagnostic = Diagnostic::warning("unused-character", "Character 'Carl' (CARL) is defined but never used".to_string())
+        .with_span(&Span::new(7, 1, 40..51));
+
+    assert!(report.push(diagnostic.clone()));
+    assert!(!report.push(diagnostic));
+    assert_eq!(report.diagnostics().len(), 1);
+}
+
+fn dialogue(speaker: &str, text: &str) -> ScriptElement {
+    ScriptElement::Dialogue {
+        speaker: speaker.to_string(),
+        text: text.to_string(),
+        actions: Vec::new(),
+        span: Span::default(),
+    }
+}
+
+#[test]
+fn findings_without_a_location_are_all_kept() {
+    // As read back from a generated AST that leaves spans out
+    let mut script = Script::new();
+    script.characters.insert(Character {
+        code: "ALICE".to_string(),
+        name: "Alice".to_string(),
+        description: None,
+        age: None,
+        aliases: Vec::new(),
+        span: Span::default(),
+    });
+    script.scenes.push(Scene {
+        location: Some("Hall".to_string()),
+        elements: vec![dialogue("ZED", "Hi."), dialogue("YAN", "Hello."), dialogue("ALICE", ""), dialogue("ALICE", "")],
+        span: Span::default(),
+    });
+
+    let report = Validator::new().validate(&script);
+    let messages: Vec<&str> = report
+        .diagnostics()
+        .iter()
+        .filter(|diagnostic| matches!(diagnostic.code, "undefined-speaker" | "empty-dialogue"))
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect();
+    assert_eq!(messages.len(), 4, "{:?}", messages);
+    assert!(messages.iter().any(|message| message.contains("ZED")));
+    assert!(messages.iter().any(|message| message.contains("YAN")));
+
+    let mut unplaced = ValidationReport::new();
+    let diagnostic = Diagnostic::error("empty-dialogue", "Dialogue for ALICE is empty".to_string());
+    assert!(unplaced.push(diagnostic.clone().with_span(&Span::default())));
+    assert!(unplaced.push(diagnostic));
+    assert_eq!(unplaced.diagnostics().len(), 2);
+}
+
+#[test]
+fn report_separates_errors_from_warnings() {
+    let report = Validator::new().validate(&parse_fixture("characters.md"));
+    let errors: Vec<&str> = report.errors().map(|diagnostic| diagnostic.code).collect();
+    let warnings: Vec<&str> = report.warnings().map(|diagnostic| diagnostic.code).collect();
+
+    assert_eq!(errors, vec!["duplicate-character", "undefined-speaker", "undefined-speaker"]);
+    assert_eq!(warnings, vec!["unused-character"]);
+    assert!(report.has_errors());
+    assert!(report.errors().all(Diagnostic::is_error));
+
+    let clean = Validator::new().validate(&parse_fixture("clean.md"));
+    assert!(clean.is_empty());
+    assert!(!clean.has_errors());
+}
+
+#[test]
+fn diagnostics_display_their_details() {
+    let diagnostic = Diagnostic::error("duplicate-character", "Duplicate character code 'BOB'".to_string())
+        .with_span(&Span::new(8, 1, 60..72))
+        .with_related(&Span::new(6, 1, 36..48), "'BOB' first defined here".to_string())
+        .with_suggestion("Remove the repeated definition".to_string());
+
+    assert_eq!(
+        diagnostic.to_string(),
+        "ERROR[duplicate-character]: Duplicate character code 'BOB' (line 8, column 1)\n\
+         \x20 Note: 'BOB' first defined here (line 6, column 1)\n\
+         \x20 Suggestion: Remove the repeated definition"
+    );
+    assert_eq!(
+        Diagnostic::warning("empty-scene", "Scene has no content".to_string()).to_string(),
+        "WARNING[empty-scene]: Scene has no content"
+    );
+}